@@ -0,0 +1,60 @@
+//! [`Arbitrary`] instances generating valid permutations and isomorphisms of bounded size.
+use proptest::prelude::*;
+
+use crate::{Isomorphism, Permutation};
+
+/// Largest permutation generated by `any::<Permutation>()`
+const MAX_PERMUTATION_SIZE: usize = 20;
+
+/// Largest node (resp. edge) permutations generated by `any::<Isomorphism>()`
+const MAX_NODES: usize = 20;
+const MAX_EDGES: usize = 15;
+
+/// Uniformly random permutations of size `n`
+fn permutation(n: usize) -> impl Strategy<Value = Permutation> {
+    Just((0..n).collect::<Vec<_>>())
+        .prop_shuffle()
+        .prop_map(|values| Permutation::new(values).expect("shuffle is a permutation"))
+}
+
+impl Arbitrary for Permutation {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (0..=MAX_PERMUTATION_SIZE)
+            .prop_flat_map(permutation)
+            .boxed()
+    }
+}
+
+impl Arbitrary for Isomorphism {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (0..=MAX_NODES, 0..=MAX_EDGES)
+            .prop_flat_map(|(n, e)| (permutation(n), permutation(e)))
+            .prop_map(|(nodes, edges)| Isomorphism { nodes, edges })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_arbitrary_permutation_is_valid(p in any::<Permutation>()) {
+            prop_assert!(p.len() <= MAX_PERMUTATION_SIZE);
+            prop_assert!(Permutation::new(p.iter().copied()).is_some());
+        }
+
+        #[test]
+        fn test_arbitrary_isomorphism_sizes(iso in any::<Isomorphism>()) {
+            prop_assert!(iso.nodes.len() <= MAX_NODES);
+            prop_assert!(iso.edges.len() <= MAX_EDGES);
+        }
+    }
+}
@@ -1,7 +1,161 @@
 use crate::permutation::*;
-use open_hypergraphs::lax::OpenHypergraph;
+use open_hypergraphs::lax::{EdgeId, Hyperedge, Hypergraph, NodeId, OpenHypergraph};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Mul;
 
-#[derive(Clone, PartialEq, Debug)]
+/// Why a pair of permutations is not an isomorphism of open hypergraphs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The node permutation has a different size to one of the hypergraphs
+    NodeCount,
+    /// The edge permutation has a different size to one of the hypergraphs
+    EdgeCount,
+    /// This node of `f` has a different label to its image
+    NodeLabel(NodeId),
+    /// This edge of `f` has a different label to its image
+    EdgeLabel(EdgeId),
+    /// This edge of `f` has a different number of sources to its image
+    Arity(EdgeId),
+    /// This edge of `f` has a different number of targets to its image
+    Coarity(EdgeId),
+    /// The source of an edge at `port` is not sent to the source of its image at `port`
+    Source { edge: EdgeId, port: usize },
+    /// The target of an edge at `port` is not sent to the target of its image at `port`
+    Target { edge: EdgeId, port: usize },
+    /// The hypergraphs' sources or targets have different lengths
+    InterfaceLength,
+    /// The source interfaces don't correspond at `port`
+    SourceInterface { port: usize },
+    /// The target interfaces don't correspond at `port`
+    TargetInterface { port: usize },
+}
+
+/// The former name of [`ValidationError`]
+#[deprecated(note = "renamed to `ValidationError`")]
+pub type StructureError = ValidationError;
+
+/// A partial assignment of the nodes and edges of `f` to those of `g`: `nodes[i] == Some(j)`
+/// means node `i` of `f` corresponds to node `j` of `g`, and `None` that it is not yet known.
+/// Likewise for edges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialIsomorphism {
+    pub nodes: Vec<Option<usize>>,
+    pub edges: Vec<Option<usize>>,
+}
+
+impl PartialIsomorphism {
+    /// Nothing assigned
+    pub fn empty(num_nodes: usize, num_edges: usize) -> Self {
+        Self {
+            nodes: vec![None; num_nodes],
+            edges: vec![None; num_edges],
+        }
+    }
+}
+
+/// Why an [`IsomorphismBuilder`] rejected a mapping, or could not build an isomorphism
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuilderError {
+    /// This node index is not less than the number of nodes
+    NodeOutOfRange(usize),
+    /// This edge index is not less than the number of edges
+    EdgeOutOfRange(usize),
+    /// This node of `f` has already been given an image
+    NodeAlreadyMapped(usize),
+    /// This edge of `f` has already been given an image
+    EdgeAlreadyMapped(usize),
+    /// This node of `g` is already the image of another node
+    NodeImageTaken(usize),
+    /// This edge of `g` is already the image of another edge
+    EdgeImageTaken(usize),
+    /// This node of `f` was never given an image
+    UnmappedNode(usize),
+    /// This edge of `f` was never given an image
+    UnmappedEdge(usize),
+}
+
+/// Build an [`Isomorphism`] one node and edge at a time, rejecting out-of-range and repeated
+/// indices as soon as they are mapped.
+#[derive(Debug, Clone)]
+pub struct IsomorphismBuilder {
+    mapping: PartialIsomorphism,
+    node_images: Vec<bool>,
+    edge_images: Vec<bool>,
+}
+
+impl IsomorphismBuilder {
+    pub fn new(num_nodes: usize, num_edges: usize) -> Self {
+        Self {
+            mapping: PartialIsomorphism::empty(num_nodes, num_edges),
+            node_images: vec![false; num_nodes],
+            edge_images: vec![false; num_edges],
+        }
+    }
+
+    /// Send node `f_id` of `f` to node `g_id` of `g`
+    pub fn map_node(&mut self, f_id: usize, g_id: usize) -> Result<&mut Self, BuilderError> {
+        for id in [f_id, g_id] {
+            if id >= self.node_images.len() {
+                return Err(BuilderError::NodeOutOfRange(id));
+            }
+        }
+        if self.mapping.nodes[f_id].is_some() {
+            return Err(BuilderError::NodeAlreadyMapped(f_id));
+        }
+        if self.node_images[g_id] {
+            return Err(BuilderError::NodeImageTaken(g_id));
+        }
+        self.mapping.nodes[f_id] = Some(g_id);
+        self.node_images[g_id] = true;
+        Ok(self)
+    }
+
+    /// Send edge `f_id` of `f` to edge `g_id` of `g`
+    pub fn map_edge(&mut self, f_id: usize, g_id: usize) -> Result<&mut Self, BuilderError> {
+        for id in [f_id, g_id] {
+            if id >= self.edge_images.len() {
+                return Err(BuilderError::EdgeOutOfRange(id));
+            }
+        }
+        if self.mapping.edges[f_id].is_some() {
+            return Err(BuilderError::EdgeAlreadyMapped(f_id));
+        }
+        if self.edge_images[g_id] {
+            return Err(BuilderError::EdgeImageTaken(g_id));
+        }
+        self.mapping.edges[f_id] = Some(g_id);
+        self.edge_images[g_id] = true;
+        Ok(self)
+    }
+
+    /// The mappings so far
+    pub fn partial(&self) -> &PartialIsomorphism {
+        &self.mapping
+    }
+
+    /// The isomorphism, once every node and edge has been mapped. Since images are never
+    /// repeated, the mappings are then necessarily bijections.
+    pub fn build(&self) -> Result<Isomorphism, BuilderError> {
+        let nodes = (0..self.mapping.nodes.len())
+            .map(|i| self.mapping.nodes[i].ok_or(BuilderError::UnmappedNode(i)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let edges = (0..self.mapping.edges.len())
+            .map(|i| self.mapping.edges[i].ok_or(BuilderError::UnmappedEdge(i)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Isomorphism {
+            nodes: Permutation::new(nodes).expect("images are distinct and in range"),
+            edges: Permutation::new(edges).expect("images are distinct and in range"),
+        })
+    }
+}
+
+/// An isomorphism `f → g` of open hypergraphs: node `i` of `f` corresponds to node `nodes[i]` of
+/// `g`, and edge `i` to edge `edges[i]`. The fields are public; [`Isomorphism::validate`] checks
+/// that they really form an isomorphism.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Isomorphism {
     pub nodes: Permutation,
     pub edges: Permutation,
@@ -15,108 +169,563 @@ impl Isomorphism {
         }
     }
 
-    // TODO: create from two permutations, where
+    /// Whether both the node and edge permutations are identities
+    pub fn is_identity(&self) -> bool {
+        self.nodes.is_identity() && self.edges.is_identity()
+    }
+
+    /// The identity isomorphism `f → f`
+    pub fn identity_for<O, A>(f: &OpenHypergraph<O, A>) -> Self {
+        Self::identity(f.hypergraph.nodes.len(), f.hypergraph.edges.len())
+    }
+
+    /// The isomorphism swapping nodes `i` and `j` and fixing all edges, or `None` if either node
+    /// is out of range.
+    pub fn swap_nodes(num_nodes: usize, num_edges: usize, i: usize, j: usize) -> Option<Self> {
+        Some(Self {
+            nodes: Permutation::transposition(num_nodes, i, j)?,
+            edges: Permutation::identity(num_edges),
+        })
+    }
+
+    /// The isomorphism sending node `i` to `node_map[&i]` and edge `i` to `edge_map[&i]`, or
+    /// `None` unless the maps are bijections on `0..num_nodes` and `0..num_edges`.
+    pub fn from_maps(
+        node_map: HashMap<usize, usize>,
+        edge_map: HashMap<usize, usize>,
+        num_nodes: usize,
+        num_edges: usize,
+    ) -> Option<Self> {
+        fn permutation(map: &HashMap<usize, usize>, n: usize) -> Option<Permutation> {
+            if map.len() != n {
+                return None;
+            }
+            let values: Option<Vec<usize>> = (0..n).map(|i| map.get(&i).copied()).collect();
+            Permutation::new(values?)
+        }
+
+        Some(Self {
+            nodes: permutation(&node_map, num_nodes)?,
+            edges: permutation(&edge_map, num_edges)?,
+        })
+    }
+
+    /// Given `iso_f: f → f'` and `iso_g: g → g'`, the isomorphism `f | g → f' | g'`.
+    /// As with `|`, the nodes and edges of `g` are numbered after those of `f`.
+    pub fn of_parallel_pair(iso_f: Isomorphism, iso_g: Isomorphism) -> Isomorphism {
+        Self {
+            nodes: iso_f.nodes.concat(&iso_g.nodes),
+            edges: iso_f.edges.concat(&iso_g.edges),
+        }
+    }
+
+    /// The number of nodes of `f` (and `g`)
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The number of edges of `f` (and `g`)
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// `(self.node_count(), self.edge_count())`
+    pub fn dim(&self) -> (usize, usize) {
+        (self.node_count(), self.edge_count())
+    }
+
+    /// The node permutation. Equivalent to field access.
+    pub fn nodes(&self) -> &Permutation {
+        &self.nodes
+    }
+
+    /// The edge permutation. Equivalent to field access.
+    pub fn edges(&self) -> &Permutation {
+        &self.edges
+    }
+
+    /// The node permutation, consuming the isomorphism.
+    pub fn project_onto_nodes(self) -> Permutation {
+        self.nodes
+    }
+
+    /// The edge permutation, consuming the isomorphism.
+    pub fn project_onto_edges(self) -> Permutation {
+        self.edges
+    }
+
+    /// [`Isomorphism::nodes`], under a name which can't be mistaken for the nodes of a
+    /// hypergraph at call sites handling both.
+    pub fn node_permutation(&self) -> &Permutation {
+        self.nodes()
+    }
+
+    /// [`Isomorphism::edges`], under a name which can't be mistaken for the edges of a
+    /// hypergraph at call sites handling both.
+    pub fn edge_permutation(&self) -> &Permutation {
+        self.edges()
+    }
+
+    /// Each node of `f` paired with its image in `g`, i.e. `(i, self.nodes[i])`
+    pub fn node_pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.nodes.iter_pairs()
+    }
+
+    /// Each edge of `f` paired with its image in `g`, i.e. `(i, self.edges[i])`
+    pub fn edge_pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.edges.iter_pairs()
+    }
+
+    /// Forget the image of every node not in `keep`, and of every edge.
+    ///
+    /// Panics if some index in `keep` is not a node.
+    pub fn restrict_nodes(&self, keep: &[usize]) -> PartialIsomorphism {
+        let mut restricted = PartialIsomorphism::empty(self.nodes.len(), self.edges.len());
+        for &i in keep {
+            restricted.nodes[i] = Some(self.nodes[i]);
+        }
+        restricted
+    }
+
+    /// Compose `self: f → g` with `other: g → h` to get an isomorphism `f → h`.
+    /// Returns `None` if the node or edge permutations have different sizes.
+    pub fn compose(&self, other: &Isomorphism) -> Option<Self> {
+        Some(Self {
+            nodes: self.nodes.compose(&other.nodes)?,
+            edges: self.edges.compose(&other.edges)?,
+        })
+    }
+
+    /// The inverse isomorphism: if `self: f → g` then `self.inverse(): g → f`.
+    pub fn inverse(&self) -> Self {
+        Self {
+            nodes: self.nodes.inverse(),
+            edges: self.edges.inverse(),
+        }
+    }
+
+    /// Compose a sequence of isomorphisms `f₀ → f₁ → … → fₙ` into a single `f₀ → fₙ`.
+    /// Returns `None` if any adjacent pair has different sizes, and `identity(0, 0)` if empty.
+    pub fn compose_many(isos: impl IntoIterator<Item = Isomorphism>) -> Option<Self> {
+        let mut isos = isos.into_iter();
+        let Some(first) = isos.next() else {
+            return Some(Self::identity(0, 0));
+        };
+        isos.try_fold(first, |acc, iso| acc.compose(&iso))
+    }
+
+    /// Like [`Isomorphism::compose_many`], but borrowing the sequence, and returning `None` if it
+    /// is empty. Each node and edge is followed through the whole chain at once, so no
+    /// intermediate composites are allocated.
+    pub fn compose_sequence(isos: &[Isomorphism]) -> Option<Self> {
+        let first = isos.first()?;
+        if isos.iter().any(|iso| iso.dim() != first.dim()) {
+            return None;
+        }
+
+        let follow = |i: usize, step: fn(&Isomorphism) -> &Permutation| {
+            isos.iter().fold(i, |i, iso| step(iso)[i])
+        };
+        let nodes = (0..first.node_count()).map(|i| follow(i, |iso| &iso.nodes));
+        let edges = (0..first.edge_count()).map(|i| follow(i, |iso| &iso.edges));
+        Some(Self {
+            nodes: Permutation::new(nodes).expect("composite of permutations"),
+            edges: Permutation::new(edges).expect("composite of permutations"),
+        })
+    }
+
+    /// The isomorphism `f → g` with the given node and edge permutations, or `None` if they
+    /// don't form one; see [`Isomorphism::validate`].
+    pub fn new<O: Eq, A: Eq>(
+        nodes: Permutation,
+        edges: Permutation,
+        f: &OpenHypergraph<O, A>,
+        g: &OpenHypergraph<O, A>,
+    ) -> Option<Self> {
+        Self::from_permutations_checked(nodes, edges, f, g).ok()
+    }
+
+    /// Like [`Isomorphism::new`], but saying which check failed.
+    pub fn from_permutations_checked<O: Eq, A: Eq>(
+        nodes: Permutation,
+        edges: Permutation,
+        f: &OpenHypergraph<O, A>,
+        g: &OpenHypergraph<O, A>,
+    ) -> Result<Self, ValidationError> {
+        let iso = Self { nodes, edges };
+        iso.validate(f, g)?;
+        Ok(iso)
+    }
+
+    /// Check that `self` is an automorphism of `f`, i.e. that `self.apply(f)` is equal to `f`.
+    pub fn is_automorphism<O: Eq, A: Eq>(&self, f: &OpenHypergraph<O, A>) -> bool {
+        self.validate(f, f).is_ok()
+    }
+
+    /// Check that `self` is an isomorphism `f → g`: it has the right sizes, preserves labels, and
+    /// sends each (ordered) list of edge sources/targets and interface nodes of `f` to exactly the
+    /// corresponding list of `g`, port by port. Otherwise, returns the first check that failed.
     pub fn validate<O: Eq, A: Eq>(
         &self,
         f: &OpenHypergraph<O, A>,
         g: &OpenHypergraph<O, A>,
-    ) -> bool {
+    ) -> Result<(), ValidationError> {
+        if self.nodes.len() != f.hypergraph.nodes.len()
+            || self.nodes.len() != g.hypergraph.nodes.len()
+        {
+            return Err(ValidationError::NodeCount);
+        }
+        if self.edges.len() != f.hypergraph.edges.len()
+            || self.edges.len() != g.hypergraph.edges.len()
+        {
+            return Err(ValidationError::EdgeCount);
+        }
+
         // Check node labels preserved
-        for (f_idx, &g_idx) in (&self.nodes).iter().enumerate() {
+        for (f_idx, &g_idx) in self.nodes.iter().enumerate() {
             if f.hypergraph.nodes[f_idx] != g.hypergraph.nodes[g_idx] {
-                return false;
+                return Err(ValidationError::NodeLabel(NodeId(f_idx)));
             }
         }
 
         // Check edge labels preserved
-        for (f_idx, &g_idx) in (&self.edges).iter().enumerate() {
+        for (f_idx, &g_idx) in self.edges.iter().enumerate() {
             if f.hypergraph.edges[f_idx] != g.hypergraph.edges[g_idx] {
-                return false;
+                return Err(ValidationError::EdgeLabel(EdgeId(f_idx)));
             }
         }
 
-        // Check adjacency structure preserved
-        for (f_edge_idx, &g_edge_idx) in (&self.edges).iter().enumerate() {
+        // Check adjacency structure preserved.
+        // Edge f_edge_idx of f corresponds to edge g_edge_idx = self.edges[f_edge_idx] of g, so it
+        // is g's adjacency at the *permuted* index which must match f's under the node permutation.
+        // Sources and targets are ordered, so each port must match, not just the set of nodes.
+        for (f_edge_idx, &g_edge_idx) in self.edges.iter().enumerate() {
+            let edge = EdgeId(f_edge_idx);
             let f_adjacency = &f.hypergraph.adjacency[f_edge_idx];
             let g_adjacency = &g.hypergraph.adjacency[g_edge_idx];
 
             // Check sources match under node permutation
             if f_adjacency.sources.len() != g_adjacency.sources.len() {
-                return false;
+                return Err(ValidationError::Arity(edge));
             }
             for (port, &f_node_idx) in f_adjacency.sources.iter().enumerate() {
                 if self.nodes[f_node_idx.0] != g_adjacency.sources[port].0 {
-                    return false;
+                    return Err(ValidationError::Source { edge, port });
                 }
             }
 
             // Check targets match under node permutation
             if f_adjacency.targets.len() != g_adjacency.targets.len() {
-                return false;
+                return Err(ValidationError::Coarity(edge));
             }
             for (port, &f_node_idx) in f_adjacency.targets.iter().enumerate() {
                 if self.nodes[f_node_idx.0] != g_adjacency.targets[port].0 {
-                    return false;
+                    return Err(ValidationError::Target { edge, port });
                 }
             }
         }
 
         // Check interfaces are compatible under node permutation
         if f.sources.len() != g.sources.len() || f.targets.len() != g.targets.len() {
-            return false;
+            return Err(ValidationError::InterfaceLength);
         }
 
         // Check sources are mapped correctly
         for (port, &f_node_idx) in f.sources.iter().enumerate() {
             if self.nodes[f_node_idx.0] != g.sources[port].0 {
-                return false;
+                return Err(ValidationError::SourceInterface { port });
             }
         }
 
         // Check targets are mapped correctly
         for (port, &f_node_idx) in f.targets.iter().enumerate() {
             if self.nodes[f_node_idx.0] != g.targets[port].0 {
-                return false;
+                return Err(ValidationError::TargetInterface { port });
             }
         }
 
-        true
+        // Conversely, every interface node of g is then the image of f's at the same port: the
+        // lengths are equal and the ports were checked one by one, so f's interfaces can't inject
+        // into just part of g's.
+        Ok(())
     }
 
-    pub fn apply<O: Clone, A: Clone>(&self, f: &OpenHypergraph<O, A>) -> OpenHypergraph<O, A> {
-        let mut result = f.clone();
+    /// The same as [`Isomorphism::validate`], which now makes these checks (including port
+    /// order) and reports the same errors.
+    #[deprecated(note = "use `validate`")]
+    pub fn check_structure_preservation<O: Eq, A: Eq>(
+        &self,
+        f: &OpenHypergraph<O, A>,
+        g: &OpenHypergraph<O, A>,
+    ) -> Result<(), ValidationError> {
+        self.validate(f, g)
+    }
 
-        // Apply node permutation to node labels
-        let original_nodes = f.hypergraph.nodes.clone();
-        for (i, &perm_idx) in (&self.nodes).iter().enumerate() {
-            result.hypergraph.nodes[perm_idx] = original_nodes[i].clone();
+    /// Build the isomorphism `f → g` with the given node bijection, inferring the edge bijection:
+    /// each edge of `f` is paired with an edge of `g` having the same label and whose sources and
+    /// targets are the images of its own.
+    /// Parallel edges (same label and adjacency) are interchangeable, and are paired in order.
+    ///
+    /// Returns `None` if `node_map` is not a bijection, some edge has no counterpart, or the
+    /// result is not a valid isomorphism.
+    pub fn from_node_bijection<O: Eq + Hash + Clone, A: Eq + Hash + Clone>(
+        f: &OpenHypergraph<O, A>,
+        g: &OpenHypergraph<O, A>,
+        node_map: HashMap<NodeId, NodeId>,
+    ) -> Option<Isomorphism> {
+        let n = f.hypergraph.nodes.len();
+        let nodes = (0..n)
+            .map(|i| node_map.get(&NodeId(i)).map(|j| j.0))
+            .collect::<Option<Vec<_>>>()?;
+        let nodes = Permutation::new(nodes)?;
+
+        // g edges keyed by (label, sources, targets), in reverse so `pop` pairs them in order
+        let mut candidates: HashMap<_, Vec<EdgeId>> = HashMap::new();
+        for (i, (label, adjacency)) in g
+            .hypergraph
+            .edges
+            .iter()
+            .zip(&g.hypergraph.adjacency)
+            .enumerate()
+            .rev()
+        {
+            let key = (label, adjacency.sources.clone(), adjacency.targets.clone());
+            candidates.entry(key).or_default().push(EdgeId(i));
         }
 
-        // Apply edge permutation to edge labels
-        let original_edges = f.hypergraph.edges.clone();
-        for (i, &perm_idx) in (&self.edges).iter().enumerate() {
-            result.hypergraph.edges[perm_idx] = original_edges[i].clone();
+        let image =
+            |ids: &[NodeId]| -> Vec<NodeId> { ids.iter().map(|i| NodeId(nodes[i.0])).collect() };
+        let mut edges = Vec::with_capacity(f.hypergraph.edges.len());
+        for (label, adjacency) in f.hypergraph.edges.iter().zip(&f.hypergraph.adjacency) {
+            let sources = image(&adjacency.sources);
+            let targets = image(&adjacency.targets);
+            let g_edge = candidates.get_mut(&(label, sources, targets))?.pop()?;
+            edges.push(g_edge.0);
         }
+        let edges = Permutation::new(edges)?;
 
-        // Update adjacency structure with node permutation
-        for adjacency in &mut result.hypergraph.adjacency {
-            for source_node in &mut adjacency.sources {
-                source_node.0 = self.nodes[source_node.0];
-            }
-            for target_node in &mut adjacency.targets {
-                target_node.0 = self.nodes[target_node.0];
-            }
+        let isomorphism = Isomorphism { nodes, edges };
+        isomorphism.validate(f, g).is_ok().then_some(isomorphism)
+    }
+
+    /// The isomorphism `f → g` pairing `f.sources[i]` with `g.sources[i]` and `f.targets[i]`
+    /// with `g.targets[i]`, and everything else as forced by the graph structure.
+    /// Unique when it exists, since `f` must be monogamous with every node and edge reachable
+    /// from the interfaces; returns `None` otherwise. Unlike
+    /// [`crate::traversal::find_isomorphism`], this never guesses a pairing for the parts of `f`
+    /// that the interfaces don't reach.
+    pub fn from_interface_correspondence<O: Eq + Hash + Clone, A: Eq + Hash + Clone>(
+        f: &OpenHypergraph<O, A>,
+        g: &OpenHypergraph<O, A>,
+    ) -> Option<Self> {
+        crate::nogood::check(f, g).ok()?;
+        let mut state = crate::traversal::SearchState::new(f, g).ok()?;
+        while state.step().ok()? {}
+        let (nodes, edges) = state.result()?;
+        let nodes = Permutation::new(nodes.into_iter().map(|node| node.0))?;
+        let edges = Permutation::new(edges.into_iter().map(|edge| edge.0))?;
+        Self::from_permutations_checked(nodes, edges, f, g).ok()
+    }
+
+    /// Check that each `isos[i]` is a valid isomorphism `hypergraphs[i] → hypergraphs[i + 1]`.
+    /// Requires exactly one more hypergraph than isomorphisms.
+    pub fn verify_composition_chain<O: Eq, A: Eq>(
+        isos: &[Isomorphism],
+        hypergraphs: &[OpenHypergraph<O, A>],
+    ) -> bool {
+        isos.len() + 1 == hypergraphs.len()
+            && isos
+                .iter()
+                .zip(hypergraphs.windows(2))
+                .all(|(iso, pair)| iso.validate(&pair[0], &pair[1]).is_ok())
+    }
+
+    /// Check that applying `self` to `f` and then applying the inverse gives back exactly `f`.
+    pub fn verify_roundtrip<O: Clone + Eq, A: Clone + Eq>(&self, f: &OpenHypergraph<O, A>) -> bool {
+        let g = self.apply(f);
+        self.inverse().apply(&g) == *f
+    }
+
+    /// For `self` an isomorphism of `f >> g`, check that it decomposes into isomorphisms of `f`
+    /// and of `g` which agree on the gluing nodes: it must map the nodes (resp. edges) of `f`
+    /// among themselves, likewise those of `g`, and send each gluing pair
+    /// `(f.targets[i], g.sources[i])` to a gluing pair.
+    ///
+    /// Nodes and edges of `f >> g` are numbered as by `|`, those of `g` after those of `f`.
+    pub fn is_compatible_with_gluing<O, A>(
+        &self,
+        f: &OpenHypergraph<O, A>,
+        g: &OpenHypergraph<O, A>,
+    ) -> bool {
+        let f_nodes = f.hypergraph.nodes.len();
+        let f_edges = f.hypergraph.edges.len();
+        if self.nodes.len() != f_nodes + g.hypergraph.nodes.len()
+            || self.edges.len() != f_edges + g.hypergraph.edges.len()
+            || f.targets.len() != g.sources.len()
+        {
+            return false;
+        }
+
+        let preserves_blocks =
+            |p: &Permutation, split: usize| p.iter_pairs().all(|(i, j)| (i < split) == (j < split));
+        if !preserves_blocks(&self.nodes, f_nodes) || !preserves_blocks(&self.edges, f_edges) {
+            return false;
         }
 
-        // Update interface indices with node permutation
-        for source in &mut result.sources {
-            source.0 = self.nodes[source.0];
+        let gluing: HashSet<(usize, usize)> = f
+            .targets
+            .iter()
+            .zip(&g.sources)
+            .map(|(u, v)| (u.0, v.0 + f_nodes))
+            .collect();
+        gluing
+            .iter()
+            .all(|&(u, v)| gluing.contains(&(self.nodes[u], self.nodes[v])))
+    }
+
+    /// Relabel `f` along this isomorphism: node (resp. edge) `i` of `f` becomes node (resp. edge)
+    /// `self.nodes[i]` (resp. `self.edges[i]`) of the result.
+    ///
+    /// Moving the label of node `i` to position `self.nodes[i]` and rewriting each reference to
+    /// node `i` as `self.nodes[i]` are the same renaming seen from two sides, so together they
+    /// apply the permutation once.
+    pub fn apply<O: Clone, A: Clone>(&self, f: &OpenHypergraph<O, A>) -> OpenHypergraph<O, A> {
+        let rename = |ids: &[NodeId]| -> Vec<NodeId> {
+            ids.iter().map(|i| NodeId(self.nodes[i.0])).collect()
+        };
+
+        // Apply node permutation to node labels
+        let nodes = self.nodes.apply_to(&f.hypergraph.nodes);
+
+        // Apply edge permutation to edge labels and adjacency, renaming nodes of the adjacency
+        let edges = self.edges.apply_to(&f.hypergraph.edges);
+        let adjacency: Vec<_> = f
+            .hypergraph
+            .adjacency
+            .iter()
+            .map(|edge| Hyperedge {
+                sources: rename(&edge.sources),
+                targets: rename(&edge.targets),
+            })
+            .collect();
+        let adjacency = self.edges.apply_to(&adjacency);
+
+        // Update quotient and interface indices with node permutation
+        let (quotient_sources, quotient_targets) = &f.hypergraph.quotient;
+        let quotient = (rename(quotient_sources), rename(quotient_targets));
+
+        OpenHypergraph {
+            sources: rename(&f.sources),
+            targets: rename(&f.targets),
+            hypergraph: Hypergraph {
+                nodes,
+                edges,
+                adjacency,
+                quotient,
+            },
         }
-        for target in &mut result.targets {
-            target.0 = self.nodes[target.0];
+    }
+
+    /// Undo [`Isomorphism::apply`]: if `self: f → g` then `self.apply_inverse(g)` is `f`.
+    ///
+    /// Same as `self.inverse().apply(g)`, but labels and edges are gathered directly, so only
+    /// the inverse node map (to rename references to nodes) is computed.
+    ///
+    /// Panics if `g` doesn't have as many nodes and edges as `self` permutes.
+    pub fn apply_inverse<O: Clone, A: Clone>(
+        &self,
+        g: &OpenHypergraph<O, A>,
+    ) -> OpenHypergraph<O, A> {
+        let wrong_size = "hypergraph must have the same size as the isomorphism";
+        let node_inverse = self.nodes.inverse();
+        let rename = |ids: &[NodeId]| -> Vec<NodeId> {
+            ids.iter().map(|i| NodeId(node_inverse[i.0])).collect()
+        };
+
+        // Node (resp. edge) i of the result is node (resp. edge) self.nodes[i] of g
+        let nodes = self
+            .nodes
+            .invert_slice(&g.hypergraph.nodes)
+            .expect(wrong_size);
+        let edges = self
+            .edges
+            .invert_slice(&g.hypergraph.edges)
+            .expect(wrong_size);
+        let adjacency = self
+            .edges
+            .invert_slice(&g.hypergraph.adjacency)
+            .expect(wrong_size)
+            .into_iter()
+            .map(|edge| Hyperedge {
+                sources: rename(&edge.sources),
+                targets: rename(&edge.targets),
+            })
+            .collect();
+
+        let (quotient_sources, quotient_targets) = &g.hypergraph.quotient;
+        let quotient = (rename(quotient_sources), rename(quotient_targets));
+
+        OpenHypergraph {
+            sources: rename(&g.sources),
+            targets: rename(&g.targets),
+            hypergraph: Hypergraph {
+                nodes,
+                edges,
+                adjacency,
+                quotient,
+            },
         }
+    }
+}
+
+/// `a * b` is `a.compose(b)`: `None` if the sizes don't match.
+impl Mul<&Isomorphism> for &Isomorphism {
+    type Output = Option<Isomorphism>;
 
-        result
+    fn mul(self, other: &Isomorphism) -> Option<Isomorphism> {
+        self.compose(other)
+    }
+}
+
+/// `a * b` is `a.compose(b)`, panicking (like `Permutation`'s `*`) if the sizes don't match.
+impl Mul<Isomorphism> for Isomorphism {
+    type Output = Isomorphism;
+
+    fn mul(self, other: Isomorphism) -> Isomorphism {
+        Isomorphism {
+            nodes: self.nodes * other.nodes,
+            edges: self.edges * other.edges,
+        }
+    }
+}
+
+/// The images of each node and edge, e.g. `nodes: [2, 0, 1] edges: [1, 0]`
+impl fmt::Display for Isomorphism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nodes: {:?} edges: {:?}", &*self.nodes, &*self.edges)
+    }
+}
+
+/// Like the derived `Debug`, but also shows each permutation in cycle notation
+impl fmt::Debug for Isomorphism {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Isomorphism")
+            .field("nodes", &WithCycles(&self.nodes))
+            .field("edges", &WithCycles(&self.edges))
+            .finish()
+    }
+}
+
+struct WithCycles<'a>(&'a Permutation);
+
+impl fmt::Debug for WithCycles<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Permutation({:?}, cycles: {:?})",
+            &**self.0,
+            self.0.cycle_notation()
+        )
     }
 }
 
@@ -124,13 +733,13 @@ impl Isomorphism {
 mod tests {
     use super::*;
 
-    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(Clone, PartialEq, Eq, Debug, Hash)]
     pub enum NodeType {
         Int,
         Float,
     }
 
-    #[derive(Clone, PartialEq, Eq, Debug)]
+    #[derive(Clone, PartialEq, Eq, Debug, Hash)]
     pub enum EdgeOp {
         Cast,
         Negate,
@@ -165,11 +774,37 @@ mod tests {
 
         // Validate that the identity isomorphism is valid for (circuit, circuit)
         assert!(
-            identity.validate(&circuit, &circuit),
+            identity.validate(&circuit, &circuit).is_ok(),
             "Identity isomorphism should be valid"
         );
     }
 
+    #[test]
+    fn test_identity_for() {
+        let circuit = cast_or_negate_then_mul();
+        assert_eq!(
+            Isomorphism::identity_for(&circuit),
+            Isomorphism::identity(7, 3)
+        );
+        assert!(Isomorphism::identity_for(&circuit).is_automorphism(&circuit));
+    }
+
+    #[test]
+    fn test_is_identity() {
+        let circuit = cast_or_negate_then_mul();
+        assert!(Isomorphism::identity_for(&circuit).is_identity());
+        assert!(Isomorphism::identity(0, 0).is_identity());
+        assert!(!Isomorphism::swap_nodes(7, 3, 0, 1).unwrap().is_identity());
+
+        // both permutations must be identities
+        let swap_edges = Isomorphism {
+            nodes: Permutation::identity(7),
+            edges: Permutation::transposition(3, 0, 2).unwrap(),
+        };
+        assert!(!swap_edges.is_identity());
+        assert!(swap_edges.compose(&swap_edges).unwrap().is_identity());
+    }
+
     #[test]
     fn test_cyclic_node_permutation_validation() {
         let circuit = cast_or_negate_then_mul();
@@ -192,8 +827,745 @@ mod tests {
 
         // Validate that the isomorphism correctly maps circuit to circuit_copy
         assert!(
-            isomorphism.validate(&circuit, &circuit_copy),
+            isomorphism.validate(&circuit, &circuit_copy).is_ok(),
             "Cyclic node permutation should be valid"
         );
     }
+
+    #[test]
+    fn test_apply_moves_labels_and_adjacency_together() {
+        let f = cast_or_negate_then_mul();
+        // neither permutation is an involution, so applying one twice would be noticed
+        let iso = Isomorphism {
+            nodes: Permutation::from_function(7, |i| (i + 2) % 7).unwrap(),
+            edges: Permutation::new([1, 2, 0]).unwrap(),
+        };
+        let g = iso.apply(&f);
+        assert!(iso.validate(&f, &g).is_ok());
+
+        for i in 0..7 {
+            assert_eq!(g.hypergraph.nodes[iso.nodes[i]], f.hypergraph.nodes[i]);
+        }
+        for e in 0..3 {
+            let (f_edge, g_edge) = (
+                &f.hypergraph.adjacency[e],
+                &g.hypergraph.adjacency[iso.edges[e]],
+            );
+            assert_eq!(g.hypergraph.edges[iso.edges[e]], f.hypergraph.edges[e]);
+            for (x, y) in f_edge.sources.iter().zip(&g_edge.sources) {
+                assert_eq!(y.0, iso.nodes[x.0]);
+                assert_eq!(g.hypergraph.nodes[y.0], f.hypergraph.nodes[x.0]);
+            }
+        }
+
+        let twice = iso.compose(&iso).unwrap();
+        assert!(twice.validate(&f, &g).is_err());
+    }
+
+    #[test]
+    fn test_apply_inverse() {
+        let f = cast_or_negate_then_mul();
+        let iso = Isomorphism {
+            nodes: Permutation::from_function(7, |i| (i + 2) % 7).unwrap(),
+            edges: Permutation::new([1, 2, 0]).unwrap(),
+        };
+        let g = iso.apply(&f);
+
+        assert_eq!(iso.apply_inverse(&g), f);
+        assert_eq!(iso.apply_inverse(&g), iso.inverse().apply(&g));
+        assert_eq!(iso.apply(&iso.apply_inverse(&f)), f);
+        assert_eq!(Isomorphism::identity_for(&f).apply_inverse(&f), f);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_inverse_wrong_size() {
+        Isomorphism::identity(3, 3).apply_inverse(&cast_or_negate_then_mul());
+    }
+
+    #[test]
+    fn test_interface_must_be_surjective() {
+        let circuit = cast_or_negate_then_mul();
+        let identity = Isomorphism::identity(7, 3);
+
+        // f's sources map onto a prefix of g's, but g has an extra source
+        let mut g = circuit.clone();
+        g.sources.push(NodeId(6));
+        let error = identity.validate(&circuit, &g);
+        assert_eq!(error, Err(ValidationError::InterfaceLength));
+
+        let mut g = circuit.clone();
+        g.targets.insert(0, NodeId(0));
+        let error = identity.validate(&circuit, &g);
+        assert_eq!(error, Err(ValidationError::InterfaceLength));
+    }
+
+    #[test]
+    fn test_validate() {
+        // The order of an edge's sources matters, even when the nodes have the same type
+        let mut f = OpenHypergraph::empty();
+        let x = f.new_node(NodeType::Float);
+        let y = f.new_node(NodeType::Float);
+        let z = f.new_node(NodeType::Float);
+        f.new_edge(EdgeOp::Mul, (vec![x, y], vec![z]));
+        let identity = Isomorphism::identity(3, 1);
+        assert_eq!(identity.validate(&f, &f), Ok(()));
+
+        // same set of sources, but in the other order
+        let mut g = f.clone();
+        g.hypergraph.adjacency[0].sources.reverse();
+        assert_eq!(
+            identity.validate(&f, &g),
+            Err(ValidationError::Source {
+                edge: EdgeId(0),
+                port: 0
+            })
+        );
+        let swap = Isomorphism::swap_nodes(3, 1, 0, 1).unwrap();
+        assert_eq!(swap.validate(&f, &g), Ok(()));
+
+        // wrong sizes are errors rather than panics
+        let too_big = Isomorphism::identity(4, 1);
+        assert_eq!(too_big.validate(&f, &f), Err(ValidationError::NodeCount));
+        assert!(too_big.validate(&f, &f).is_err());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_check_structure_preservation() {
+        let mut f = OpenHypergraph::empty();
+        let x = f.new_node(NodeType::Float);
+        let y = f.new_node(NodeType::Float);
+        let z = f.new_node(NodeType::Float);
+        f.new_edge(EdgeOp::Mul, (vec![x, y], vec![z]));
+        let mut g = f.clone();
+        g.hypergraph.adjacency[0].sources.reverse();
+
+        let identity = Isomorphism::identity(3, 1);
+        assert_eq!(identity.check_structure_preservation(&f, &f), Ok(()));
+        let error: Result<(), StructureError> = identity.check_structure_preservation(&f, &g);
+        assert_eq!(error, identity.validate(&f, &g));
+    }
+
+    #[test]
+    fn test_validate_rejects_shuffled_interface() {
+        // Two parallel wires: swapping them preserves labels and (the empty) adjacency, but not the
+        // order of the interfaces
+        let mut f = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        let x = f.new_node(NodeType::Int);
+        let y = f.new_node(NodeType::Int);
+        f.sources = vec![x, y];
+        f.targets = vec![x, y];
+
+        let swap = Isomorphism::swap_nodes(2, 0, 0, 1).unwrap();
+        assert_eq!(
+            swap.validate(&f, &f),
+            Err(ValidationError::SourceInterface { port: 0 })
+        );
+
+        // only the targets are shuffled
+        let mut g = f.clone();
+        g.sources = vec![y, x];
+        assert_eq!(
+            swap.validate(&f, &g),
+            Err(ValidationError::TargetInterface { port: 0 })
+        );
+        assert_eq!(Isomorphism::identity(2, 0).validate(&f, &f), Ok(()));
+    }
+
+    #[test]
+    fn test_debug_shows_cycles() {
+        let isomorphism = Isomorphism {
+            nodes: Permutation::new([0, 2, 1, 3]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        assert_eq!(
+            format!("{isomorphism:?}"),
+            "Isomorphism { nodes: Permutation([0, 2, 1, 3], cycles: \"(1 2)\"), \
+             edges: Permutation([1, 0], cycles: \"(0 1)\") }"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let isomorphism = Isomorphism {
+            nodes: Permutation::new([2, 0, 1]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        assert_eq!(isomorphism.to_string(), "nodes: [2, 0, 1] edges: [1, 0]");
+        assert_eq!(
+            Isomorphism::identity(0, 0).to_string(),
+            "nodes: [] edges: []"
+        );
+    }
+
+    #[test]
+    fn test_verify_composition_chain() {
+        let circuit = cast_or_negate_then_mul();
+        let num_nodes = circuit.hypergraph.nodes.len();
+        let num_edges = circuit.hypergraph.edges.len();
+
+        // a chain of cyclic node permutations
+        let step = Isomorphism {
+            nodes: Permutation::new((0..num_nodes).map(|i| (i + 1) % num_nodes)).unwrap(),
+            edges: Permutation::identity(num_edges),
+        };
+        let mut chain = vec![circuit];
+        for _ in 0..3 {
+            chain.push(step.apply(chain.last().unwrap()));
+        }
+        let isos = vec![step.clone(); 3];
+
+        assert!(Isomorphism::verify_composition_chain(&isos, &chain));
+        assert!(Isomorphism::verify_composition_chain(&[], &chain[..1]));
+
+        // wrong number of hypergraphs
+        assert!(!Isomorphism::verify_composition_chain(&isos, &chain[..3]));
+        assert!(!Isomorphism::verify_composition_chain::<NodeType, EdgeOp>(
+            &[],
+            &[]
+        ));
+
+        // a step which doesn't map between its hypergraphs
+        let identity = Isomorphism::identity(num_nodes, num_edges);
+        let isos = vec![step.clone(), identity, step];
+        assert!(!Isomorphism::verify_composition_chain(&isos, &chain));
+    }
+
+    #[test]
+    fn test_counts() {
+        let iso = Isomorphism::identity_for(&cast_or_negate_then_mul());
+        assert_eq!(iso.node_count(), 7);
+        assert_eq!(iso.edge_count(), 3);
+        assert_eq!(iso.dim(), (7, 3));
+        assert_eq!(Isomorphism::identity(0, 0).dim(), (0, 0));
+    }
+
+    #[test]
+    fn test_projections() {
+        let iso = Isomorphism::swap_nodes(3, 2, 0, 1).unwrap();
+        assert_eq!(iso.node_permutation(), &iso.nodes);
+        assert_eq!(iso.edge_permutation(), &iso.edges);
+        assert_eq!(iso.clone().project_onto_nodes(), iso.nodes);
+        assert_eq!(iso.clone().project_onto_edges(), iso.edges);
+    }
+
+    #[test]
+    fn test_is_automorphism() {
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let mut f = &negate | &negate;
+        f.sources.clear();
+        f.targets.clear();
+
+        let swap = Isomorphism {
+            nodes: Permutation::new([2, 3, 0, 1]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        assert!(swap.is_automorphism(&f));
+        assert!(swap.apply(&f) == f);
+
+        // only swapping the nodes disconnects them from their edges
+        let nodes_only = Isomorphism {
+            nodes: swap.nodes.clone(),
+            edges: Permutation::identity(2),
+        };
+        assert!(!nodes_only.is_automorphism(&f));
+    }
+
+    #[test]
+    fn test_swap_nodes() {
+        let swap = Isomorphism::swap_nodes(3, 2, 0, 2).unwrap();
+        assert_eq!(swap.nodes, Permutation::new([2, 1, 0]).unwrap());
+        assert_eq!(swap.edges, Permutation::identity(2));
+
+        // node indices are bounded by the number of nodes, not edges
+        assert!(Isomorphism::swap_nodes(3, 1, 0, 2).is_some());
+        assert_eq!(Isomorphism::swap_nodes(3, 2, 0, 3), None);
+    }
+
+    #[test]
+    fn test_of_parallel_pair() {
+        let f = cast_or_negate_then_mul();
+        let g =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+
+        let iso_f = Isomorphism {
+            nodes: Permutation::new([1, 0, 2, 3, 4, 5, 6]).unwrap(),
+            edges: Permutation::new([1, 0, 2]).unwrap(),
+        };
+        let iso_g = Isomorphism::swap_nodes(2, 1, 0, 1).unwrap();
+
+        let iso = Isomorphism::of_parallel_pair(iso_f.clone(), iso_g.clone());
+        assert_eq!(
+            iso.nodes,
+            Permutation::new([1, 0, 2, 3, 4, 5, 6, 8, 7]).unwrap()
+        );
+        assert_eq!(iso.edges, Permutation::new([1, 0, 2, 3]).unwrap());
+        assert!(iso.apply(&(&f | &g)) == &iso_f.apply(&f) | &iso_g.apply(&g));
+    }
+
+    #[test]
+    fn test_verify_roundtrip() {
+        let circuit = cast_or_negate_then_mul();
+        let iso = Isomorphism {
+            nodes: Permutation::new([3, 4, 5, 6, 0, 1, 2]).unwrap(),
+            edges: Permutation::new([2, 0, 1]).unwrap(),
+        };
+        assert!(iso.verify_roundtrip(&circuit));
+        assert!(iso.inverse().verify_roundtrip(&iso.apply(&circuit)));
+    }
+
+    #[test]
+    fn test_is_compatible_with_gluing() {
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let two_negates = &negate | &negate;
+
+        // Swap the two lanes of both halves of `two_negates >> two_negates`
+        let both = Isomorphism {
+            nodes: Permutation::new([2, 3, 0, 1, 6, 7, 4, 5]).unwrap(),
+            edges: Permutation::new([1, 0, 3, 2]).unwrap(),
+        };
+        assert!(both.is_compatible_with_gluing(&two_negates, &two_negates));
+
+        // Swapping only the first half pulls apart the gluing pairs
+        let first = Isomorphism {
+            nodes: Permutation::new([2, 3, 0, 1, 4, 5, 6, 7]).unwrap(),
+            edges: Permutation::new([1, 0, 2, 3]).unwrap(),
+        };
+        assert!(!first.is_compatible_with_gluing(&two_negates, &two_negates));
+
+        // Exchanging the halves does not decompose
+        let halves = Isomorphism {
+            nodes: Permutation::new([4, 5, 6, 7, 0, 1, 2, 3]).unwrap(),
+            edges: Permutation::new([2, 3, 0, 1]).unwrap(),
+        };
+        assert!(!halves.is_compatible_with_gluing(&two_negates, &two_negates));
+
+        // Wrong size
+        let identity = Isomorphism::identity(4, 2);
+        assert!(!identity.is_compatible_with_gluing(&two_negates, &two_negates));
+    }
+
+    #[test]
+    fn test_new() {
+        let f = cast_or_negate_then_mul();
+        let nodes = Permutation::from_function(7, |i| (i + 1) % 7).unwrap();
+        let g = Isomorphism {
+            nodes: nodes.clone(),
+            edges: Permutation::identity(3),
+        }
+        .apply(&f);
+
+        let iso = Isomorphism::new(nodes.clone(), Permutation::identity(3), &f, &g).unwrap();
+        assert_eq!(iso.nodes, nodes);
+        assert_eq!(
+            Isomorphism::new(Permutation::identity(7), Permutation::identity(3), &f, &f),
+            Some(Isomorphism::identity(7, 3))
+        );
+
+        // right shape, wrong structure
+        assert_eq!(
+            Isomorphism::new(Permutation::identity(7), Permutation::identity(3), &f, &g),
+            None
+        );
+        // wrong shape
+        assert_eq!(
+            Isomorphism::new(nodes, Permutation::identity(2), &f, &g),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_permutations_checked() {
+        let f = cast_or_negate_then_mul();
+        let nodes = Permutation::from_function(7, |i| (i + 1) % 7).unwrap();
+        let g = Isomorphism {
+            nodes: nodes.clone(),
+            edges: Permutation::identity(3),
+        }
+        .apply(&f);
+
+        let checked =
+            Isomorphism::from_permutations_checked(nodes.clone(), Permutation::identity(3), &f, &g);
+        assert_eq!(checked.unwrap().nodes, nodes);
+
+        let wrong_edges = Permutation::new([1, 0, 2]).unwrap();
+        assert_eq!(
+            Isomorphism::from_permutations_checked(nodes, wrong_edges, &f, &g),
+            Err(ValidationError::EdgeLabel(EdgeId(0)))
+        );
+        assert_eq!(
+            Isomorphism::from_permutations_checked(
+                Permutation::identity(6),
+                Permutation::identity(3),
+                &f,
+                &g
+            ),
+            Err(ValidationError::NodeCount)
+        );
+    }
+
+    #[test]
+    fn test_from_maps() {
+        let nodes = HashMap::from([(0, 1), (1, 2), (2, 0)]);
+        let edges = HashMap::from([(0, 1), (1, 0)]);
+        assert_eq!(
+            Isomorphism::from_maps(nodes.clone(), edges.clone(), 3, 2),
+            Some(Isomorphism {
+                nodes: Permutation::new([1, 2, 0]).unwrap(),
+                edges: Permutation::new([1, 0]).unwrap(),
+            })
+        );
+        assert_eq!(
+            Isomorphism::from_maps(HashMap::new(), HashMap::new(), 0, 0),
+            Some(Isomorphism::identity(0, 0))
+        );
+
+        // wrong size
+        assert_eq!(
+            Isomorphism::from_maps(nodes.clone(), edges.clone(), 4, 2),
+            None
+        );
+        assert_eq!(
+            Isomorphism::from_maps(nodes.clone(), edges.clone(), 3, 1),
+            None
+        );
+        // not injective
+        let collapsing = HashMap::from([(0, 1), (1, 1), (2, 0)]);
+        assert_eq!(
+            Isomorphism::from_maps(collapsing, edges.clone(), 3, 2),
+            None
+        );
+        // key out of range
+        let shifted = HashMap::from([(1, 1), (2, 2), (3, 0)]);
+        assert_eq!(Isomorphism::from_maps(shifted, edges, 3, 2), None);
+    }
+
+    #[test]
+    fn test_compose() {
+        let f = cast_or_negate_then_mul();
+        let a = Isomorphism {
+            nodes: Permutation::from_function(7, |i| (i + 1) % 7).unwrap(),
+            edges: Permutation::identity(3),
+        };
+        let b = Isomorphism {
+            nodes: Permutation::transposition(7, 2, 5).unwrap(),
+            edges: Permutation::new([2, 0, 1]).unwrap(),
+        };
+        let g = a.apply(&f);
+        let h = b.apply(&g);
+
+        let composite = a.compose(&b).unwrap();
+        assert!(composite.validate(&f, &h).is_ok());
+        assert_eq!(composite.apply(&f), h);
+
+        for iso in [&a, &b, &composite] {
+            assert_eq!(
+                iso.compose(&iso.inverse()),
+                Some(Isomorphism::identity(7, 3))
+            );
+        }
+
+        // sizes must agree on both nodes and edges
+        assert_eq!(a.compose(&Isomorphism::identity(6, 3)), None);
+        assert_eq!(a.compose(&Isomorphism::identity(7, 2)), None);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let f = cast_or_negate_then_mul();
+        let iso = Isomorphism {
+            nodes: Permutation::from_function(7, |i| (i + 3) % 7).unwrap(),
+            edges: Permutation::new([1, 2, 0]).unwrap(),
+        };
+        let g = iso.apply(&f);
+
+        let inverse = iso.inverse();
+        assert!(inverse.validate(&g, &f).is_ok());
+        assert_eq!(inverse.apply(&g), f);
+        assert_eq!(inverse.inverse(), iso);
+
+        assert_eq!(iso.compose(&inverse), Some(Isomorphism::identity(7, 3)));
+        assert_eq!(inverse.compose(&iso), Some(Isomorphism::identity(7, 3)));
+        assert_eq!(
+            Isomorphism::identity(7, 3).inverse(),
+            Isomorphism::identity(7, 3)
+        );
+    }
+
+    #[test]
+    fn test_pairs() {
+        let iso = Isomorphism {
+            nodes: Permutation::new([2, 0, 1]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        assert_eq!(
+            iso.node_pairs().collect::<Vec<_>>(),
+            vec![(0, 2), (1, 0), (2, 1)]
+        );
+        assert_eq!(iso.edge_pairs().collect::<Vec<_>>(), vec![(0, 1), (1, 0)]);
+
+        let node_map: HashMap<NodeId, NodeId> = iso
+            .node_pairs()
+            .map(|(i, j)| (NodeId(i), NodeId(j)))
+            .collect();
+        assert_eq!(node_map[&NodeId(0)], NodeId(2));
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let a = Isomorphism::swap_nodes(3, 2, 0, 1).unwrap();
+        let b = Isomorphism {
+            nodes: Permutation::new([1, 0, 2]).unwrap(),
+            edges: Permutation::identity(2),
+        };
+        let state = RandomState::new();
+        assert_eq!(a, b);
+        assert_eq!(state.hash_one(&a), state.hash_one(&b));
+
+        let set: HashSet<Isomorphism> = [a.clone(), Isomorphism::identity(3, 2), b, a]
+            .into_iter()
+            .collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&Isomorphism::identity(3, 2)));
+    }
+
+    #[test]
+    fn test_restrict_nodes() {
+        let iso = Isomorphism {
+            nodes: Permutation::new([2, 0, 3, 1]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        assert_eq!(
+            iso.restrict_nodes(&[0, 3]),
+            PartialIsomorphism {
+                nodes: vec![Some(2), None, None, Some(1)],
+                edges: vec![None, None],
+            }
+        );
+        assert_eq!(iso.restrict_nodes(&[]), PartialIsomorphism::empty(4, 2));
+        assert_eq!(iso.restrict_nodes(&[1, 1]).nodes[1], Some(0));
+    }
+
+    #[test]
+    fn test_builder() {
+        let mut builder = IsomorphismBuilder::new(3, 2);
+        builder
+            .map_node(0, 2)
+            .unwrap()
+            .map_node(1, 0)
+            .unwrap()
+            .map_edge(0, 1)
+            .unwrap();
+        assert_eq!(builder.partial().nodes, vec![Some(2), Some(0), None]);
+        assert_eq!(builder.build(), Err(BuilderError::UnmappedNode(2)));
+
+        builder.map_node(2, 1).unwrap();
+        assert_eq!(builder.build(), Err(BuilderError::UnmappedEdge(1)));
+
+        builder.map_edge(1, 0).unwrap();
+        assert_eq!(
+            builder.build(),
+            Ok(Isomorphism {
+                nodes: Permutation::new([2, 0, 1]).unwrap(),
+                edges: Permutation::new([1, 0]).unwrap(),
+            })
+        );
+        assert_eq!(
+            IsomorphismBuilder::new(0, 0).build(),
+            Ok(Isomorphism::identity(0, 0))
+        );
+    }
+
+    #[test]
+    fn test_builder_rejects_eagerly() {
+        let mut builder = IsomorphismBuilder::new(3, 1);
+        builder.map_node(0, 1).unwrap();
+
+        assert_eq!(
+            builder.map_node(3, 0).unwrap_err(),
+            BuilderError::NodeOutOfRange(3)
+        );
+        assert_eq!(
+            builder.map_node(2, 5).unwrap_err(),
+            BuilderError::NodeOutOfRange(5)
+        );
+        assert_eq!(
+            builder.map_node(0, 2).unwrap_err(),
+            BuilderError::NodeAlreadyMapped(0)
+        );
+        assert_eq!(
+            builder.map_node(2, 1).unwrap_err(),
+            BuilderError::NodeImageTaken(1)
+        );
+        assert_eq!(
+            builder.map_edge(1, 0).unwrap_err(),
+            BuilderError::EdgeOutOfRange(1)
+        );
+
+        builder.map_edge(0, 0).unwrap();
+        assert_eq!(
+            builder.map_edge(0, 0).unwrap_err(),
+            BuilderError::EdgeAlreadyMapped(0)
+        );
+        // rejected mappings leave the builder unchanged
+        assert_eq!(builder.partial().nodes, vec![Some(1), None, None]);
+    }
+
+    #[test]
+    fn test_mul() {
+        let a = Isomorphism {
+            nodes: Permutation::new([1, 2, 0]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        let b = Isomorphism::swap_nodes(3, 2, 0, 2).unwrap();
+
+        assert_eq!(&a * &b, a.compose(&b));
+        assert_eq!(a.clone() * b.clone(), a.compose(&b).unwrap());
+        assert_eq!(&a * &Isomorphism::identity(3, 3), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mul_mismatched_sizes() {
+        let _ = Isomorphism::identity(3, 2) * Isomorphism::identity(3, 3);
+    }
+
+    #[test]
+    fn test_compose_many() {
+        let rotate = Isomorphism {
+            nodes: Permutation::new([1, 2, 0]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        let chain = |k| std::iter::repeat_n(rotate.clone(), k);
+
+        assert_eq!(
+            Isomorphism::compose_many([]),
+            Some(Isomorphism::identity(0, 0))
+        );
+        assert_eq!(Isomorphism::compose_many(chain(1)), Some(rotate.clone()));
+        assert_eq!(Isomorphism::compose_many(chain(2)), rotate.compose(&rotate));
+
+        // the node rotation has order 3 and the edge swap has order 2
+        let ten = Isomorphism::compose_many(chain(10)).unwrap();
+        assert_eq!(ten.nodes, Permutation::new([1, 2, 0]).unwrap());
+        assert_eq!(ten.edges, Permutation::identity(2));
+
+        // incompatible sizes
+        let mismatched = [rotate.clone(), Isomorphism::identity(2, 2)];
+        assert_eq!(Isomorphism::compose_many(mismatched), None);
+    }
+
+    #[test]
+    fn test_compose_sequence() {
+        let rotate = Isomorphism {
+            nodes: Permutation::new([1, 2, 0]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        let swap = Isomorphism::swap_nodes(3, 2, 0, 1).unwrap();
+        let sequence = [rotate.clone(), swap.clone(), rotate.clone(), rotate.clone()];
+
+        assert_eq!(
+            Isomorphism::compose_sequence(&sequence),
+            Isomorphism::compose_many(sequence.clone())
+        );
+        assert_eq!(
+            Isomorphism::compose_sequence(&sequence[..1]),
+            Some(rotate.clone())
+        );
+        assert_eq!(Isomorphism::compose_sequence(&[]), None);
+
+        let mismatched = [rotate, swap, Isomorphism::identity(3, 3)];
+        assert_eq!(Isomorphism::compose_sequence(&mismatched), None);
+    }
+
+    #[test]
+    fn test_from_interface_correspondence() {
+        let circuit = cast_or_negate_then_mul();
+        let iso = Isomorphism {
+            nodes: Permutation::new([2, 3, 0, 1, 4, 5, 6]).unwrap(),
+            edges: Permutation::new([1, 0, 2]).unwrap(),
+        };
+        let swapped = iso.apply(&circuit);
+        assert_eq!(
+            Isomorphism::from_interface_correspondence(&circuit, &swapped),
+            Some(iso)
+        );
+
+        // pairing the interfaces differently forces an incompatible node labelling
+        let mut reordered = swapped;
+        reordered.sources.reverse();
+        assert_eq!(
+            Isomorphism::from_interface_correspondence(&circuit, &reordered),
+            None
+        );
+
+        // the two isolated nodes could be paired either way, so the interfaces don't decide them
+        let mut f = circuit.clone();
+        f.new_node(NodeType::Float);
+        f.new_node(NodeType::Float);
+        assert!(crate::traversal::find_isomorphism(&f, &f).is_ok());
+        assert_eq!(Isomorphism::from_interface_correspondence(&f, &f), None);
+    }
+
+    #[test]
+    fn test_from_node_bijection() {
+        let circuit = cast_or_negate_then_mul();
+        let num_nodes = circuit.hypergraph.nodes.len();
+        let num_edges = circuit.hypergraph.edges.len();
+
+        let expected = Isomorphism {
+            nodes: Permutation::new((0..num_nodes).map(|i| (i + 1) % num_nodes)).unwrap(),
+            edges: Permutation::identity(num_edges),
+        };
+        let circuit_copy = expected.apply(&circuit);
+
+        let node_map: HashMap<NodeId, NodeId> = expected
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &j)| (NodeId(i), NodeId(j)))
+            .collect();
+        let found = Isomorphism::from_node_bijection(&circuit, &circuit_copy, node_map.clone());
+        assert_eq!(found, Some(expected));
+
+        // the identity node map is not compatible with the edges of circuit_copy
+        let identity: HashMap<NodeId, NodeId> =
+            (0..num_nodes).map(|i| (NodeId(i), NodeId(i))).collect();
+        assert!(Isomorphism::from_node_bijection(&circuit, &circuit_copy, identity).is_none());
+
+        // incomplete node maps are rejected
+        let mut partial = node_map;
+        partial.remove(&NodeId(0));
+        assert!(Isomorphism::from_node_bijection(&circuit, &circuit_copy, partial).is_none());
+    }
+
+    #[test]
+    fn test_edge_permutation_validation() {
+        let cast =
+            OpenHypergraph::singleton(EdgeOp::Cast, vec![NodeType::Int], vec![NodeType::Float]);
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let circuit = &cast | &negate;
+
+        // Same circuit, but with the order of edges reversed
+        let mut reversed = circuit.clone();
+        reversed.hypergraph.edges.reverse();
+        reversed.hypergraph.adjacency.reverse();
+
+        let num_nodes = circuit.hypergraph.nodes.len();
+        let swap_edges = Isomorphism {
+            nodes: Permutation::identity(num_nodes),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        assert!(swap_edges.validate(&circuit, &reversed).is_ok());
+
+        // Adjacency must be compared at the permuted edge index
+        let identity = Isomorphism::identity(num_nodes, 2);
+        assert!(identity.validate(&circuit, &reversed).is_err());
+    }
 }
@@ -1,9 +1,24 @@
+// Trace logging of the search, compiled away entirely unless the `log` feature is enabled.
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::trace!($($arg)*);
+    };
+}
+
 pub mod isomorphism;
+pub mod nogood;
 pub mod permutation;
+pub mod propagator;
 pub mod traversal;
 
-// fast nogood checks
-mod nogood;
+// random permutations and isomorphisms for property tests
+#[cfg(feature = "proptest")]
+mod arbitrary;
+
+// serialize permutations as plain lists, validating them when deserializing
+#[cfg(feature = "serde")]
+mod serialize;
 
 pub use isomorphism::Isomorphism;
 pub use permutation::Permutation;
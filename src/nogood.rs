@@ -1,30 +1,89 @@
-use open_hypergraphs::lax::OpenHypergraph;
+//! Fast checks ruling out an isomorphism between two open hypergraphs
+use open_hypergraphs::lax::{NodeId, OpenHypergraph};
 use std::hash::Hash;
 
+/// Why a nogood check failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NogoodReason {
+    /// The hypergraphs have different numbers of nodes
+    NodeCount,
+    /// The hypergraphs have different numbers of edges
+    EdgeCount,
+    /// The multisets of node labels differ
+    NodeLabels,
+    /// The multisets of edge labels differ
+    EdgeLabels,
+    /// The source interfaces have different lengths
+    SourceLength,
+    /// The target interfaces have different lengths
+    TargetLength,
+    /// The source interfaces have different types
+    SourceTypes,
+    /// The target interfaces have different types
+    TargetTypes,
+}
+
+/// Quickly rule out an isomorphism `f → g`.
+/// Passing is necessary (but not sufficient) for `f` and `g` to be isomorphic.
 // TODO: also check arity/coarity and types of each edge
-pub(crate) fn nogood<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+pub fn check<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
     f: &OpenHypergraph<O, A>,
     g: &OpenHypergraph<O, A>,
-) -> Option<()> {
+) -> Result<(), NogoodReason> {
+    if f.hypergraph.nodes.len() != g.hypergraph.nodes.len() {
+        return Err(NogoodReason::NodeCount);
+    }
+
+    if f.hypergraph.edges.len() != g.hypergraph.edges.len() {
+        return Err(NogoodReason::EdgeCount);
+    }
+
     if !is_sorted_equal(&f.hypergraph.nodes, &g.hypergraph.nodes) {
-        return None;
+        return Err(NogoodReason::NodeLabels);
     }
 
     if !is_sorted_equal(&f.hypergraph.edges, &g.hypergraph.edges) {
-        return None;
+        return Err(NogoodReason::EdgeLabels);
     }
 
     // check interfaces are equal sizes and types
-    use open_hypergraphs::category::*;
-    if f.source() != g.source() {
-        return None;
+    if f.sources.len() != g.sources.len() {
+        return Err(NogoodReason::SourceLength);
+    }
+
+    if f.targets.len() != g.targets.len() {
+        return Err(NogoodReason::TargetLength);
     }
 
-    if f.target() != g.target() {
-        return None;
+    if !same_types(f, &f.sources, g, &g.sources) {
+        return Err(NogoodReason::SourceTypes);
     }
 
-    Some(())
+    if !same_types(f, &f.targets, g, &g.targets) {
+        return Err(NogoodReason::TargetTypes);
+    }
+
+    Ok(())
+}
+
+/// Check that the sources (resp. targets) of `f` and `g` have the same types, position by
+/// position.
+/// Like [`check`], this is necessary (but not sufficient) for `f` and `g` to be isomorphic, and
+/// is cheap enough to run before constructing a [`crate::traversal::SearchState`].
+pub fn check_interface_types<O: Eq, A>(f: &OpenHypergraph<O, A>, g: &OpenHypergraph<O, A>) -> bool {
+    same_types(f, &f.sources, g, &g.sources) && same_types(f, &f.targets, g, &g.targets)
+}
+
+/// Whether `f_nodes` of `f` and `g_nodes` of `g` have the same labels in the same order
+fn same_types<O: Eq, A>(
+    f: &OpenHypergraph<O, A>,
+    f_nodes: &[NodeId],
+    g: &OpenHypergraph<O, A>,
+    g_nodes: &[NodeId],
+) -> bool {
+    let f_types = f_nodes.iter().map(|i| &f.hypergraph.nodes[i.0]);
+    let g_types = g_nodes.iter().map(|i| &g.hypergraph.nodes[i.0]);
+    f_types.eq(g_types)
 }
 
 /// Check that two vecs are equal once sorted (exact length and elements)
@@ -54,6 +113,66 @@ fn is_sorted_equal<T: Eq + Hash>(x: &Vec<T>, y: &Vec<T>) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_check_reasons() {
+        let f = OpenHypergraph::<u8, u8>::singleton(0, vec![0], vec![1]);
+        assert_eq!(check(&f, &f), Ok(()));
+
+        let g = OpenHypergraph::singleton(1, vec![0], vec![1]);
+        assert_eq!(check(&f, &g), Err(NogoodReason::EdgeLabels));
+
+        let g = &f | &OpenHypergraph::identity(vec![0]);
+        assert_eq!(check(&f, &g), Err(NogoodReason::NodeCount));
+
+        let mut g = f.clone();
+        g.new_edge(0, (vec![], vec![]));
+        assert_eq!(check(&f, &g), Err(NogoodReason::EdgeCount));
+
+        let g = OpenHypergraph::singleton(0, vec![0], vec![2]);
+        assert_eq!(check(&f, &g), Err(NogoodReason::NodeLabels));
+
+        let mut g = f.clone();
+        g.sources = g.targets.clone();
+        assert_eq!(check(&f, &g), Err(NogoodReason::SourceTypes));
+
+        let mut g = f.clone();
+        g.targets = g.sources.clone();
+        assert_eq!(check(&f, &g), Err(NogoodReason::TargetTypes));
+    }
+
+    #[test]
+    fn test_check_interface_lengths() {
+        let f = OpenHypergraph::<u8, u8>::singleton(0, vec![0], vec![1]);
+
+        let mut g = f.clone();
+        g.sources.push(NodeId(0));
+        assert_eq!(check(&f, &g), Err(NogoodReason::SourceLength));
+
+        let mut g = f.clone();
+        g.targets.clear();
+        assert_eq!(check(&f, &g), Err(NogoodReason::TargetLength));
+
+        // checked before the types of the interfaces
+        let mut g = f.clone();
+        g.targets = vec![NodeId(0), NodeId(0)];
+        assert_eq!(check(&f, &g), Err(NogoodReason::TargetLength));
+    }
+
+    #[test]
+    fn test_check_interface_types() {
+        let f = OpenHypergraph::<u8, u8>::singleton(0, vec![0, 1], vec![1]);
+        assert!(check_interface_types(&f, &f));
+
+        // same multiset of source types, but in a different order
+        let mut g = f.clone();
+        g.sources.reverse();
+        assert!(!check_interface_types(&f, &g));
+
+        let mut g = f.clone();
+        g.targets.clear();
+        assert!(!check_interface_types(&f, &g));
+    }
+
     #[test]
     fn test_is_sorted_equal_empty_vectors() {
         let x: Vec<i32> = vec![];
@@ -1,7 +1,18 @@
-use std::fmt::Debug;
-use std::ops::Deref;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{self, Debug};
+use std::ops::{Deref, Index, Mul};
+use std::slice::SliceIndex;
 
-#[derive(Clone, PartialEq, Debug)]
+/// Two permutations that should have had the same length didn't
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthMismatch {
+    pub left: usize,
+    pub right: usize,
+}
+
+/// Permutations are ordered lexicographically by their images `self[0], self[1], ...`, so
+/// [`Permutation::identity`] is the least permutation of its size.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct Permutation(Vec<usize>);
 
 impl Permutation {
@@ -32,6 +43,403 @@ impl Permutation {
     pub fn identity(size: usize) -> Self {
         Self((0..size).collect())
     }
+
+    /// A uniformly random permutation of size `n`, sampled with a Fisher-Yates shuffle.
+    #[cfg(feature = "rand")]
+    pub fn random<R: rand::Rng>(n: usize, rng: &mut R) -> Self {
+        let mut values: Vec<usize> = (0..n).collect();
+        for i in (1..n).rev() {
+            values.swap(i, rng.random_range(0..=i));
+        }
+        Self(values)
+    }
+
+    /// The number of elements permuted
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether `self` is the (unique) permutation of no elements
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether `self[i] == i` for every `i`
+    pub fn is_identity(&self) -> bool {
+        self.iter_pairs().all(|(i, j)| i == j)
+    }
+
+    /// Whether `self` is its own inverse, i.e. `self[self[i]] == i` for every `i`
+    pub fn is_involution(&self) -> bool {
+        self.0.iter().enumerate().all(|(i, &j)| self.0[j] == i)
+    }
+
+    /// The permutation `i ↦ f(i)` of size `n`, or `None` if `f` is not a bijection on `0..n`.
+    pub fn from_function(n: usize, f: impl Fn(usize) -> usize) -> Option<Self> {
+        Self::new((0..n).map(f))
+    }
+
+    /// The permutation of size `n` swapping `i` and `j`, or `None` if either is out of range.
+    pub fn transposition(n: usize, i: usize, j: usize) -> Option<Self> {
+        if i >= n || j >= n {
+            return None;
+        }
+        let mut values: Vec<usize> = (0..n).collect();
+        values.swap(i, j);
+        Some(Self(values))
+    }
+
+    /// Compose `self` then `other`, so that `r[i] = other[self[i]]`.
+    /// Returns `None` if the permutations have different lengths.
+    pub fn compose(&self, other: &Permutation) -> Option<Self> {
+        self.try_compose(other).ok()
+    }
+
+    /// Like [`Permutation::compose`], but reporting both lengths if they differ.
+    pub fn try_compose(&self, other: &Permutation) -> Result<Self, LengthMismatch> {
+        if self.len() != other.len() {
+            return Err(LengthMismatch {
+                left: self.len(),
+                right: other.len(),
+            });
+        }
+        Ok(self.compose_unchecked(other))
+    }
+
+    /// The inverse permutation, mapping `self[i]` back to `i`.
+    pub fn inverse(&self) -> Self {
+        let mut inverse = vec![0; self.len()];
+        for (i, j) in self.iter_pairs() {
+            inverse[j] = i;
+        }
+        Self(inverse)
+    }
+
+    /// Compose with the transposition of `i` and `j`, or `None` if either is out of range.
+    pub fn swap_transpose(&self, i: usize, j: usize) -> Option<Self> {
+        let transposition = Self::transposition(self.len(), i, j)?;
+        Some(self.compose_unchecked(&transposition))
+    }
+
+    /// The group generated by `generators`, found by breadth-first search from the identity.
+    /// An empty list of generators gives the trivial group of size-0 permutations.
+    ///
+    /// Panics if the generators have different lengths.
+    pub fn generate_group(generators: &[Permutation]) -> HashSet<Permutation> {
+        let n = generators.first().map_or(0, |p| p.len());
+        assert!(
+            generators.iter().all(|p| p.len() == n),
+            "generators must all have the same length"
+        );
+
+        let identity = Self::identity(n);
+        let mut group = HashSet::from([identity.clone()]);
+        let mut frontier = VecDeque::from([identity]);
+
+        while let Some(element) = frontier.pop_front() {
+            for generator in generators {
+                let product = element.compose_unchecked(generator);
+                if group.insert(product.clone()) {
+                    frontier.push_back(product);
+                }
+            }
+        }
+
+        group
+    }
+
+    /// All pairs `(i, self[i])`, including fixed points
+    pub fn iter_pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.0.iter().copied().enumerate()
+    }
+
+    /// Pairs `(i, self[i])` with `i != self[i]`, i.e. skipping fixed points
+    pub fn iter_nontrivial_pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.iter_pairs().filter(|(i, j)| i != j)
+    }
+
+    /// Move `data[i]` to position `self[i]`, or `None` if `data` has a different length.
+    pub fn apply_to_slice<T: Clone>(&self, data: &[T]) -> Option<Vec<T>> {
+        if data.len() != self.len() {
+            return None;
+        }
+        Some(self.inverse().iter().map(|&i| data[i].clone()).collect())
+    }
+
+    /// Like [`Permutation::apply_to_slice`], but panics if `data` has a different length.
+    pub fn apply_to<T: Clone>(&self, data: &[T]) -> Vec<T> {
+        self.apply_to_slice(data)
+            .expect("data must have the same length as the permutation")
+    }
+
+    /// Write `src[i]` to `dst[self[i]]`, without allocating.
+    ///
+    /// Panics if `src` or `dst` has a different length.
+    pub fn apply_into<T: Clone>(&self, src: &[T], dst: &mut [T]) {
+        assert_eq!(
+            src.len(),
+            self.len(),
+            "src must have the same length as the permutation"
+        );
+        assert_eq!(
+            dst.len(),
+            self.len(),
+            "dst must have the same length as the permutation"
+        );
+        for (i, j) in self.iter_pairs() {
+            dst[j] = src[i].clone();
+        }
+    }
+
+    /// Undo [`Permutation::apply_to_slice`], moving `data[self[i]]` back to position `i`.
+    /// Same as `self.inverse().apply_to_slice(data)`, but without computing the inverse.
+    /// Returns `None` if `data` has a different length.
+    pub fn invert_slice<T: Clone>(&self, data: &[T]) -> Option<Vec<T>> {
+        if data.len() != self.len() {
+            return None;
+        }
+        Some(self.0.iter().map(|&j| data[j].clone()).collect())
+    }
+
+    /// `self` acting on `0..self.len()` alongside `other` acting on the indices after it.
+    pub(crate) fn concat(&self, other: &Permutation) -> Self {
+        let offset = self.len();
+        Self(
+            self.0
+                .iter()
+                .copied()
+                .chain(other.0.iter().map(|&i| i + offset))
+                .collect(),
+        )
+    }
+
+    /// Indices `i` with `self[i] == i`
+    pub fn fixed_points(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(|&i| self.0[i] == i)
+    }
+
+    /// Indices `i` with `self[i] != i`, i.e. those moved by `self`, in increasing order.
+    /// Empty for the identity.
+    pub fn support(&self) -> Vec<usize> {
+        (0..self.len()).filter(|&i| self.0[i] != i).collect()
+    }
+
+    /// The largest distance `|i - self[i]|` any index is moved, or 0 if empty.
+    pub fn max_displacement(&self) -> usize {
+        self.iter_pairs()
+            .map(|(i, j)| i.abs_diff(j))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The mean distance `|i - self[i]|` an index is moved, or 0 if empty.
+    pub fn average_displacement(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let total: usize = self.iter_pairs().map(|(i, j)| i.abs_diff(j)).sum();
+        total as f64 / self.len() as f64
+    }
+
+    /// The Lehmer code: `code[i]` is the number of `j > i` with `self[j] < self[i]`, so that
+    /// `code[i] < n - i`.
+    pub fn lehmer_code(&self) -> Vec<usize> {
+        (0..self.len())
+            .map(|i| self.0[i + 1..].iter().filter(|&&x| x < self.0[i]).count())
+            .collect()
+    }
+
+    /// Inverse of [`Permutation::lehmer_code`], or `None` if some `code[i] >= n - i`.
+    pub fn from_lehmer_code(code: &[usize]) -> Option<Self> {
+        let mut remaining: Vec<usize> = (0..code.len()).collect();
+        let mut values = Vec::with_capacity(code.len());
+        for &c in code {
+            if c >= remaining.len() {
+                return None;
+            }
+            values.push(remaining.remove(c));
+        }
+        Some(Self(values))
+    }
+
+    /// The index of `self` among all permutations of its size in lexicographic order, i.e. its
+    /// Lehmer code read as a number in the factorial number system.
+    ///
+    /// Panics if the rank doesn't fit in a `usize`, which is possible once `n > 20`.
+    pub fn rank(&self) -> usize {
+        let n = self.len();
+        self.lehmer_code()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &digit)| digit > 0)
+            .map(|(i, &digit)| factorial(n - 1 - i).and_then(|f| f.checked_mul(digit)))
+            .try_fold(0usize, |acc, x| acc.checked_add(x?))
+            .expect("rank overflows usize")
+    }
+
+    /// The permutation of size `n` with the given [`Permutation::rank`], or `None` if
+    /// `rank >= n!`.
+    pub fn from_rank(mut rank: usize, n: usize) -> Option<Self> {
+        if factorial(n).is_some_and(|total| rank >= total) {
+            return None;
+        }
+        let code: Vec<usize> = (0..n)
+            .map(|i| match factorial(n - 1 - i) {
+                Some(f) => {
+                    let digit = rank / f;
+                    rank %= f;
+                    digit
+                }
+                // (n - 1 - i)! > usize::MAX >= rank
+                None => 0,
+            })
+            .collect();
+        Self::from_lehmer_code(&code)
+    }
+
+    /// The disjoint cycles of `self`, each starting from its smallest element and sorted by that
+    /// element. Fixed points are included as cycles of length 1, so every index appears once.
+    pub fn cycle_decomposition(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.len()];
+        let mut cycles = vec![];
+
+        for start in 0..self.len() {
+            if seen[start] {
+                continue;
+            }
+
+            let mut cycle = vec![];
+            let mut i = start;
+            while !seen[i] {
+                seen[i] = true;
+                cycle.push(i);
+                i = self.0[i];
+            }
+            cycles.push(cycle);
+        }
+
+        cycles
+    }
+
+    /// The parity of `self`: `true` if it is even, i.e. a product of an even number of
+    /// transpositions. A cycle of length `k` is a product of `k - 1` transpositions.
+    pub fn sign(&self) -> bool {
+        let transpositions: usize = self.cycle_decomposition().iter().map(|c| c.len() - 1).sum();
+        transpositions.is_multiple_of(2)
+    }
+
+    /// The smallest `k > 0` such that composing `self` with itself `k` times is the identity: the
+    /// least common multiple of the cycle lengths.
+    pub fn order(&self) -> usize {
+        fn gcd(a: usize, b: usize) -> usize {
+            if b == 0 { a } else { gcd(b, a % b) }
+        }
+        self.cycle_decomposition()
+            .iter()
+            .map(Vec::len)
+            .fold(1, |acc, k| acc / gcd(acc, k) * k)
+    }
+
+    /// The permutation of size `n` with the given disjoint cycles, each `[a, b, …, z]` sending
+    /// `a ↦ b ↦ … ↦ z ↦ a`; indices in no cycle are fixed. Inverse to
+    /// [`Permutation::cycle_decomposition`].
+    /// Returns `None` if an index is out of range or appears more than once.
+    pub fn from_cycles<C: AsRef<[usize]>>(cycles: &[C], n: usize) -> Option<Self> {
+        let mut values: Vec<usize> = (0..n).collect();
+        let mut seen = vec![false; n];
+        for cycle in cycles {
+            let cycle = cycle.as_ref();
+            for (k, &i) in cycle.iter().enumerate() {
+                if i >= n || seen[i] {
+                    return None;
+                }
+                seen[i] = true;
+                values[i] = cycle[(k + 1) % cycle.len()];
+            }
+        }
+        Some(Self(values))
+    }
+
+    /// Parse the cycle notation written by `Display`, e.g. `(0 2 1)(3)`.
+    /// Every index must appear, including fixed points, since this determines the size; `()` is
+    /// the permutation of size 0.
+    pub fn from_display(s: &str) -> Option<Self> {
+        let mut cycles = vec![];
+        let mut rest = s.trim();
+        while !rest.is_empty() {
+            let (cycle, tail) = rest.strip_prefix('(')?.split_once(')')?;
+            let cycle: Vec<usize> = cycle
+                .split_whitespace()
+                .map(|i| i.parse().ok())
+                .collect::<Option<_>>()?;
+            cycles.push(cycle);
+            rest = tail.trim_start();
+        }
+        let n = cycles.iter().map(Vec::len).sum();
+        Self::from_cycles(&cycles, n)
+    }
+
+    /// `self` composed with itself `k` times, or its inverse `-k` times if `k` is negative.
+    /// Each element in a cycle of length `l` moves `k mod l` steps around it.
+    pub fn power(&self, k: i64) -> Self {
+        let mut values = vec![0; self.len()];
+        for cycle in self.cycle_decomposition() {
+            let l = cycle.len() as i64;
+            let steps = k.rem_euclid(l) as usize;
+            for (position, &i) in cycle.iter().enumerate() {
+                values[i] = cycle[(position + steps) % cycle.len()];
+            }
+        }
+        Self(values)
+    }
+
+    /// The conjugate `by⁻¹ * self * by`: `self` with its elements relabelled by `by`, so
+    /// `self[i] = j` becomes `by[i] ↦ by[j]`. Returns `None` if the lengths differ.
+    pub fn conjugate(&self, by: &Permutation) -> Option<Self> {
+        by.inverse().compose(self)?.compose(by)
+    }
+
+    /// Cycle notation omitting fixed points, e.g. `(0 2 1)`; the identity is written `()`.
+    pub(crate) fn cycle_notation(&self) -> String {
+        let mut result = String::new();
+        for cycle in self.cycle_decomposition() {
+            if cycle.len() > 1 {
+                let cycle: Vec<String> = cycle.iter().map(|i| i.to_string()).collect();
+                result.push_str(&format!("({})", cycle.join(" ")));
+            }
+        }
+
+        if result.is_empty() {
+            result.push_str("()");
+        }
+        result
+    }
+
+    /// Compose `self` then `other`, assuming both have the same length.
+    fn compose_unchecked(&self, other: &Permutation) -> Self {
+        debug_assert_eq!(self.len(), other.len());
+        Self(self.0.iter().map(|&i| other.0[i]).collect())
+    }
+}
+
+/// `n!`, or `None` if it doesn't fit in a `usize`
+fn factorial(n: usize) -> Option<usize> {
+    (1..=n).try_fold(1usize, |acc, i| acc.checked_mul(i))
+}
+
+/// Cycle notation including fixed points, e.g. `(0 2 1)(3)`, or `()` if empty.
+/// Parse with [`Permutation::from_display`].
+impl fmt::Display for Permutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "()");
+        }
+        for cycle in self.cycle_decomposition() {
+            let cycle: Vec<String> = cycle.iter().map(|i| i.to_string()).collect();
+            write!(f, "({})", cycle.join(" "))?;
+        }
+        Ok(())
+    }
 }
 
 impl Deref for Permutation {
@@ -42,6 +450,73 @@ impl Deref for Permutation {
     }
 }
 
+/// `p[i]` is the image of `i`, and ranges like `p[1..]` give slices of images, panicking when
+/// out of bounds like slice indexing.
+impl<I: SliceIndex<[usize]>> Index<I> for Permutation {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &I::Output {
+        &self.0[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a Permutation {
+    type Item = usize;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, usize>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter().copied()
+    }
+}
+
+impl IntoIterator for Permutation {
+    type Item = usize;
+    type IntoIter = std::vec::IntoIter<usize>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// `p * q` is `p.compose(q)`: apply `p`, then `q`.
+/// Panics if the permutations have different lengths.
+impl Mul<&Permutation> for &Permutation {
+    type Output = Permutation;
+
+    fn mul(self, other: &Permutation) -> Permutation {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "cannot compose permutations of different lengths"
+        );
+        self.compose_unchecked(other)
+    }
+}
+
+impl Mul<Permutation> for Permutation {
+    type Output = Permutation;
+
+    fn mul(self, other: Permutation) -> Permutation {
+        &self * &other
+    }
+}
+
+impl Mul<&Permutation> for Permutation {
+    type Output = Permutation;
+
+    fn mul(self, other: &Permutation) -> Permutation {
+        &self * other
+    }
+}
+
+impl Mul<Permutation> for &Permutation {
+    type Output = Permutation;
+
+    fn mul(self, other: Permutation) -> Permutation {
+        self * &other
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +546,556 @@ mod tests {
         assert!(Permutation::new((0..3).collect::<Vec<_>>()).is_some());
         assert!(Permutation::new([2, 1, 0].iter().copied()).is_some());
     }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+
+        assert_eq!(Permutation::random(0, &mut rng), Permutation::identity(0));
+        assert_eq!(Permutation::random(1, &mut rng), Permutation::identity(1));
+
+        let mut seen = HashSet::new();
+        for _ in 0..200 {
+            let p = Permutation::random(3, &mut rng);
+            assert!(Permutation::new(p.to_vec()).is_some());
+            seen.insert(p);
+        }
+        // every permutation of size 3 should turn up
+        assert_eq!(seen.len(), 6);
+    }
+
+    #[test]
+    fn test_ord() {
+        use std::collections::{BTreeSet, HashMap};
+
+        let p = Permutation::new([0, 2, 1]).unwrap();
+        let q = Permutation::new([1, 0, 2]).unwrap();
+        assert!(p < q);
+        assert!(Permutation::identity(3) < p);
+
+        // lexicographic order agrees with rank
+        let all: Vec<Permutation> = (0..24)
+            .map(|k| Permutation::from_rank(k, 4).unwrap())
+            .collect();
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(all.iter().min(), Some(&Permutation::identity(4)));
+
+        let set: BTreeSet<Permutation> = [q.clone(), p.clone(), q.clone()].into_iter().collect();
+        assert_eq!(
+            set.into_iter().collect::<Vec<_>>(),
+            vec![p.clone(), q.clone()]
+        );
+
+        let mut orders = HashMap::new();
+        orders.insert(p.clone(), p.order());
+        assert_eq!(orders.get(&p), Some(&2));
+    }
+
+    #[test]
+    fn test_len() {
+        assert_eq!(Permutation::new([2, 0, 1]).unwrap().len(), 3);
+        assert!(!Permutation::identity(1).is_empty());
+        assert_eq!(Permutation::identity(0).len(), 0);
+        assert!(Permutation::identity(0).is_empty());
+    }
+
+    #[test]
+    fn test_is_identity() {
+        assert!(Permutation::identity(3).is_identity());
+        assert!(Permutation::identity(0).is_identity());
+        assert!(!Permutation::transposition(3, 1, 2).unwrap().is_identity());
+    }
+
+    #[test]
+    fn test_is_involution() {
+        assert!(Permutation::transposition(4, 1, 3).unwrap().is_involution());
+        assert!(Permutation::new([1, 0, 3, 2]).unwrap().is_involution());
+        assert!(Permutation::identity(3).is_involution());
+        assert!(Permutation::identity(0).is_involution());
+
+        let cycle = Permutation::new([1, 2, 0]).unwrap();
+        assert!(!cycle.is_involution());
+        assert_ne!(cycle.compose(&cycle).unwrap(), Permutation::identity(3));
+    }
+
+    #[test]
+    fn test_from_function() {
+        assert_eq!(
+            Permutation::from_function(4, |i| (i + 1) % 4),
+            Permutation::new([1, 2, 3, 0])
+        );
+        assert_eq!(
+            Permutation::from_function(0, |i| i),
+            Some(Permutation::identity(0))
+        );
+
+        // out of range
+        assert_eq!(Permutation::from_function(3, |i| i + 1), None);
+        // not distinct
+        assert_eq!(Permutation::from_function(3, |i| i / 2), None);
+    }
+
+    #[test]
+    fn test_compose() {
+        let p = Permutation::new([1, 2, 0, 3]).unwrap();
+        let q = Permutation::new([3, 0, 1, 2]).unwrap();
+        let r = Permutation::compose(&p, &q).unwrap();
+        for i in 0..4 {
+            assert_eq!(r[i], q[p[i]]);
+        }
+        assert_eq!(r, Permutation::new([0, 1, 3, 2]).unwrap());
+
+        // composition is not commutative
+        assert_ne!(q.compose(&p), Some(r));
+
+        assert_eq!(p.compose(&Permutation::identity(4)), Some(p.clone()));
+        assert_eq!(p.compose(&Permutation::identity(3)), None);
+    }
+
+    #[test]
+    fn test_index() {
+        let p = Permutation::new([2, 0, 1]).unwrap();
+        assert_eq!((p[0], p[1], p[2]), (2, 0, 1));
+        assert_eq!(&p[1..], &[0, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_bounds() {
+        let _ = Permutation::identity(3)[3];
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let p = Permutation::new([2, 0, 1]).unwrap();
+
+        let mut images = Vec::new();
+        for image in &p {
+            images.push(image);
+        }
+        assert_eq!(images, vec![2, 0, 1]);
+        assert_eq!(
+            p.iter().enumerate().collect::<Vec<_>>(),
+            vec![(0, &2), (1, &0), (2, &1)]
+        );
+        assert_eq!(p.into_iter().collect::<Vec<_>>(), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn test_try_compose() {
+        let p = Permutation::new([1, 2, 0]).unwrap();
+        let q = Permutation::new([0, 2, 1]).unwrap();
+        assert_eq!(Permutation::try_compose(&p, &q), Ok(&p * &q));
+        assert_eq!(
+            p.try_compose(&Permutation::identity(5)),
+            Err(LengthMismatch { left: 3, right: 5 })
+        );
+    }
+
+    #[test]
+    fn test_mul() {
+        let p = Permutation::new([1, 2, 0, 3]).unwrap();
+        let q = Permutation::new([3, 0, 1, 2]).unwrap();
+        let r = p.compose(&q).unwrap();
+        assert_eq!(&p * &q, r);
+        assert_eq!(p.clone() * &q, r);
+        assert_eq!(&p * q.clone(), r);
+        assert_eq!(p.clone() * q.clone(), r);
+
+        let generators = [p.clone(), q.clone(), p.inverse()];
+        let product = generators
+            .iter()
+            .fold(Permutation::identity(4), |acc, g| &acc * g);
+        assert_eq!(
+            product,
+            p.compose(&q).unwrap().compose(&p.inverse()).unwrap()
+        );
+        assert_eq!(&p * &p.inverse(), Permutation::identity(4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mul_mismatched_lengths() {
+        let _ = &Permutation::identity(2) * &Permutation::identity(3);
+    }
+
+    #[test]
+    fn test_transposition() {
+        assert_eq!(&*Permutation::transposition(3, 0, 2).unwrap(), &[2, 1, 0]);
+        assert_eq!(&*Permutation::transposition(3, 1, 1).unwrap(), &[0, 1, 2]);
+        assert!(Permutation::transposition(3, 0, 3).is_none());
+        assert!(Permutation::transposition(0, 0, 0).is_none());
+    }
+
+    #[test]
+    fn test_swap_transpose() {
+        let p = Permutation::new([1, 2, 0]).unwrap();
+
+        // swapping the images 0 and 1
+        assert_eq!(&*p.swap_transpose(0, 1).unwrap(), &[0, 2, 1]);
+
+        // swapping twice is a no-op
+        let q = p
+            .swap_transpose(0, 2)
+            .unwrap()
+            .swap_transpose(0, 2)
+            .unwrap();
+        assert_eq!(q, p);
+
+        assert!(p.swap_transpose(0, 3).is_none());
+    }
+
+    #[test]
+    fn test_generate_group() {
+        assert_eq!(Permutation::generate_group(&[]).len(), 1);
+
+        // a single transposition generates a group of order 2
+        let t = Permutation::transposition(4, 1, 3).unwrap();
+        let group = Permutation::generate_group(std::slice::from_ref(&t));
+        assert_eq!(group, HashSet::from([Permutation::identity(4), t]));
+
+        // a 3-cycle generates the cyclic group of order 3
+        let c = Permutation::new([1, 2, 0]).unwrap();
+        assert_eq!(Permutation::generate_group(&[c]).len(), 3);
+
+        // adjacent transpositions generate the whole symmetric group
+        let generators: Vec<_> = (0..3)
+            .map(|i| Permutation::transposition(4, i, i + 1).unwrap())
+            .collect();
+        let group = Permutation::generate_group(&generators);
+        assert_eq!(group.len(), 24);
+
+        // ... which is closed under composition
+        for p in &group {
+            for q in &group {
+                assert!(group.contains(&p.compose_unchecked(q)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter_pairs() {
+        let p = Permutation::new([0, 2, 1, 3]).unwrap();
+        assert_eq!(
+            p.iter_pairs().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 2), (2, 1), (3, 3)]
+        );
+        assert_eq!(
+            p.iter_nontrivial_pairs().collect::<Vec<_>>(),
+            vec![(1, 2), (2, 1)]
+        );
+        assert_eq!(Permutation::identity(3).iter_nontrivial_pairs().count(), 0);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let p = Permutation::new([1, 2, 0]).unwrap();
+        assert_eq!(p.inverse(), Permutation::new([2, 0, 1]).unwrap());
+        assert_eq!(p.compose(&p.inverse()), Some(Permutation::identity(3)));
+
+        let p = Permutation::new([4, 0, 3, 5, 1, 2]).unwrap();
+        assert_eq!(p.inverse().compose(&p), Some(Permutation::identity(6)));
+        assert_eq!(p.inverse().inverse(), p);
+        assert_eq!(Permutation::identity(0).inverse(), Permutation::identity(0));
+    }
+
+    #[test]
+    fn test_apply_to_slice() {
+        let p = Permutation::new([1, 2, 0]).unwrap();
+        assert_eq!(
+            p.apply_to_slice(&['a', 'b', 'c']),
+            Some(vec!['c', 'a', 'b'])
+        );
+        assert_eq!(p.apply_to_slice(&['a', 'b']), None);
+    }
+
+    #[test]
+    fn test_apply_to() {
+        let p = Permutation::new([1, 2, 0]).unwrap();
+        assert_eq!(p.apply_to(&['a', 'b', 'c']), vec!['c', 'a', 'b']);
+
+        let mut dst = ['_'; 3];
+        p.apply_into(&['a', 'b', 'c'], &mut dst);
+        assert_eq!(dst, ['c', 'a', 'b']);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_to_mismatched_length() {
+        Permutation::identity(2).apply_to(&[0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_into_mismatched_length() {
+        Permutation::identity(2).apply_into(&[0, 1], &mut [0; 3]);
+    }
+
+    #[test]
+    fn test_invert_slice() {
+        let p = Permutation::new([1, 2, 0]).unwrap();
+        let data = ['a', 'b', 'c'];
+        assert_eq!(p.invert_slice(&data), p.inverse().apply_to_slice(&data));
+        assert_eq!(
+            p.invert_slice(&p.apply_to_slice(&data).unwrap()),
+            Some(data.to_vec())
+        );
+        assert_eq!(p.invert_slice(&['a']), None);
+    }
+
+    #[test]
+    fn test_concat() {
+        let p = Permutation::new([1, 0]).unwrap();
+        let q = Permutation::new([2, 0, 1]).unwrap();
+        assert_eq!(p.concat(&q), Permutation::new([1, 0, 4, 2, 3]).unwrap());
+        assert_eq!(Permutation::identity(0).concat(&q), q);
+    }
+
+    #[test]
+    fn test_fixed_points_and_support() {
+        let p = Permutation::new([0, 2, 1, 3, 5, 4]).unwrap();
+        assert_eq!(p.fixed_points().collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(p.support(), vec![1, 2, 4, 5]);
+
+        assert_eq!(Permutation::identity(3).fixed_points().count(), 3);
+        assert_eq!(Permutation::identity(3).support(), Vec::<usize>::new());
+        assert_eq!(Permutation::identity(0).support(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_displacement() {
+        let p = Permutation::new([3, 1, 2, 0]).unwrap();
+        assert_eq!(p.max_displacement(), 3);
+        assert_eq!(p.average_displacement(), 1.5);
+
+        assert_eq!(Permutation::identity(5).max_displacement(), 0);
+        assert_eq!(Permutation::identity(0).average_displacement(), 0.0);
+    }
+
+    #[test]
+    fn test_lehmer_code() {
+        let p = Permutation::new([2, 0, 3, 1]).unwrap();
+        assert_eq!(p.lehmer_code(), vec![2, 0, 1, 0]);
+        assert_eq!(Permutation::from_lehmer_code(&[2, 0, 1, 0]), Some(p));
+
+        assert_eq!(Permutation::identity(4).lehmer_code(), vec![0; 4]);
+        assert_eq!(
+            Permutation::from_lehmer_code(&[]),
+            Some(Permutation::identity(0))
+        );
+        assert_eq!(Permutation::from_lehmer_code(&[0, 2, 0]), None);
+        // the last digit must be 0
+        assert_eq!(Permutation::from_lehmer_code(&[0, 0, 1]), None);
+        assert_eq!(Permutation::from_lehmer_code(&[3, 0, 0]), None);
+
+        // every code of length 4 decodes to a distinct permutation
+        let mut decoded = HashSet::new();
+        for a in 0..4 {
+            for b in 0..3 {
+                for c in 0..2 {
+                    let p = Permutation::from_lehmer_code(&[a, b, c, 0]).unwrap();
+                    assert_eq!(p.lehmer_code(), vec![a, b, c, 0]);
+                    decoded.insert(p);
+                }
+            }
+        }
+        assert_eq!(decoded.len(), 24);
+    }
+
+    #[test]
+    fn test_rank() {
+        // all permutations of size 4, in lexicographic order
+        for k in 0..24 {
+            let p = Permutation::from_rank(k, 4).unwrap();
+            assert_eq!(p.rank(), k);
+            if k > 0 {
+                let previous = Permutation::from_rank(k - 1, 4).unwrap();
+                assert!(previous.0 < p.0);
+            }
+        }
+        assert_eq!(Permutation::from_rank(0, 4), Some(Permutation::identity(4)));
+        assert_eq!(
+            Permutation::from_rank(23, 4),
+            Some(Permutation::new([3, 2, 1, 0]).unwrap())
+        );
+        assert_eq!(Permutation::from_rank(24, 4), None);
+        assert_eq!(Permutation::from_rank(0, 0), Some(Permutation::identity(0)));
+
+        // n! overflows, but small ranks are still fine
+        let p = Permutation::from_rank(1, 30).unwrap();
+        assert_eq!(p.rank(), 1);
+        assert_eq!(p, Permutation::transposition(30, 28, 29).unwrap());
+    }
+
+    #[test]
+    fn test_cycle_decomposition() {
+        let p = Permutation::new([2, 0, 1, 3]).unwrap();
+        assert_eq!(p.cycle_decomposition(), vec![vec![0, 2, 1], vec![3]]);
+
+        let p = Permutation::new([3, 4, 2, 5, 1, 0]).unwrap();
+        assert_eq!(
+            p.cycle_decomposition(),
+            vec![vec![0, 3, 5], vec![1, 4], vec![2]]
+        );
+
+        assert_eq!(
+            Permutation::identity(3).cycle_decomposition(),
+            vec![vec![0], vec![1], vec![2]]
+        );
+        assert!(Permutation::identity(0).cycle_decomposition().is_empty());
+    }
+
+    #[test]
+    fn test_sign() {
+        assert!(Permutation::identity(4).sign());
+        assert!(Permutation::identity(0).sign());
+
+        // transpositions are odd
+        for (i, j) in [(0, 1), (0, 3), (1, 2)] {
+            assert!(!Permutation::transposition(4, i, j).unwrap().sign());
+        }
+
+        // 3-cycles are even
+        assert!(Permutation::new([1, 2, 0, 3]).unwrap().sign());
+        assert!(Permutation::new([0, 3, 1, 2]).unwrap().sign());
+
+        // a 4-cycle is odd, and the sign is multiplicative
+        let p = Permutation::new([1, 2, 3, 0]).unwrap();
+        let q = Permutation::transposition(4, 0, 2).unwrap();
+        assert!(!p.sign());
+        assert!(p.compose(&q).unwrap().sign());
+    }
+
+    #[test]
+    fn test_order() {
+        assert_eq!(Permutation::identity(4).order(), 1);
+        assert_eq!(Permutation::identity(0).order(), 1);
+        assert_eq!(Permutation::transposition(4, 1, 3).unwrap().order(), 2);
+
+        // a 2-cycle and a 3-cycle
+        let p = Permutation::new([1, 0, 3, 4, 2]).unwrap();
+        assert_eq!(p.order(), 6);
+        let mut q = Permutation::identity(5);
+        for k in 1..=6 {
+            q = q.compose(&p).unwrap();
+            assert_eq!(q == Permutation::identity(5), k == 6);
+        }
+
+        // two 2-cycles and a 4-cycle
+        let p = Permutation::new([1, 0, 3, 2, 5, 6, 7, 4]).unwrap();
+        assert_eq!(p.order(), 4);
+    }
+
+    #[test]
+    fn test_from_cycles() {
+        let p = Permutation::from_cycles(&[vec![0, 2, 1], vec![3, 4]], 5).unwrap();
+        assert_eq!(p, Permutation::new([2, 0, 1, 4, 3]).unwrap());
+
+        // round trip through the decomposition, including fixed points
+        let p = Permutation::new([3, 4, 2, 5, 1, 0]).unwrap();
+        assert_eq!(
+            Permutation::from_cycles(&p.cycle_decomposition(), 6),
+            Some(p)
+        );
+
+        let none: &[[usize; 0]] = &[];
+        assert_eq!(
+            Permutation::from_cycles(none, 3),
+            Some(Permutation::identity(3))
+        );
+
+        // out of range
+        assert_eq!(Permutation::from_cycles(&[[0, 3]], 3), None);
+        // not disjoint
+        assert_eq!(Permutation::from_cycles(&[vec![0, 1], vec![1, 2]], 3), None);
+        assert_eq!(Permutation::from_cycles(&[[0, 1, 0]], 3), None);
+    }
+
+    #[test]
+    fn test_power() {
+        // a 2-cycle and a 3-cycle
+        let p = Permutation::new([1, 0, 3, 4, 2]).unwrap();
+        assert_eq!(p.power(p.order() as i64), Permutation::identity(5));
+        assert_eq!(p.power(0), Permutation::identity(5));
+        assert_eq!(p.power(1), p);
+        assert_eq!(p.power(-1), p.inverse());
+        assert_eq!(p.power(2), p.compose(&p).unwrap());
+        assert_eq!(p.power(-7), p.inverse().power(7));
+        assert_eq!(p.power(i64::MIN), p.power(i64::MIN % 6));
+
+        assert_eq!(Permutation::identity(0).power(3), Permutation::identity(0));
+    }
+
+    #[test]
+    fn test_conjugate() {
+        let p = Permutation::new([1, 2, 0, 3]).unwrap();
+        let q = Permutation::new([3, 0, 1, 2]).unwrap();
+
+        for by in [&p, &q, &Permutation::identity(4)] {
+            assert_eq!(
+                Permutation::identity(4).conjugate(by),
+                Some(Permutation::identity(4))
+            );
+        }
+
+        // relabelling preserves cycle structure
+        let c = p.conjugate(&q).unwrap();
+        for i in 0..4 {
+            assert_eq!(c[q[i]], q[p[i]]);
+        }
+        assert_eq!(c.cycle_notation(), "(0 1 3)");
+        assert_eq!(p.conjugate(&Permutation::identity(4)), Some(p.clone()));
+
+        assert_eq!(p.conjugate(&Permutation::identity(3)), None);
+    }
+
+    #[test]
+    fn test_cycle_notation() {
+        assert_eq!(Permutation::identity(3).cycle_notation(), "()");
+        assert_eq!(Permutation::identity(0).cycle_notation(), "()");
+        assert_eq!(
+            Permutation::new([2, 0, 1, 3]).unwrap().cycle_notation(),
+            "(0 2 1)"
+        );
+        assert_eq!(
+            Permutation::new([1, 0, 3, 2]).unwrap().cycle_notation(),
+            "(0 1)(2 3)"
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let p = Permutation::new([2, 0, 1, 3]).unwrap();
+        assert_eq!(p.to_string(), "(0 2 1)(3)");
+        assert_eq!(Permutation::identity(2).to_string(), "(0)(1)");
+        assert_eq!(Permutation::identity(0).to_string(), "()");
+
+        for p in [
+            p,
+            Permutation::new([3, 4, 2, 5, 1, 0]).unwrap(),
+            Permutation::identity(0),
+            Permutation::identity(1),
+        ] {
+            assert_eq!(Permutation::from_display(&p.to_string()), Some(p));
+        }
+
+        assert_eq!(
+            Permutation::from_display(" (1 0) (2) "),
+            Permutation::new([1, 0, 2])
+        );
+        // missing fixed point
+        assert_eq!(Permutation::from_display("(0 3 1)"), None);
+        // malformed
+        assert_eq!(Permutation::from_display("(0 1"), None);
+        assert_eq!(Permutation::from_display("0 1"), None);
+        assert_eq!(Permutation::from_display("(0 x)"), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_generate_group_mismatched_lengths() {
+        Permutation::generate_group(&[Permutation::identity(2), Permutation::identity(3)]);
+    }
 }
@@ -1,7 +1,8 @@
 use std::collections::HashSet;
+use std::fmt::{self, Debug};
 use std::hash::Hash;
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq)]
 pub enum Constraint<T: Hash + Eq> {
     Any,
     Set(HashSet<T>),
@@ -17,7 +18,7 @@ impl<T: Clone + Hash + Eq> Constraint<T> {
     pub fn intersection(&mut self, s: HashSet<T>) {
         *self = match self {
             Self::Any => Self::Set(s),
-            Self::Set(t) => Self::Set(s.intersection(&t).cloned().collect()),
+            Self::Set(t) => Self::Set(s.intersection(t).cloned().collect()),
         };
     }
 
@@ -25,6 +26,22 @@ impl<T: Clone + Hash + Eq> Constraint<T> {
         self.intersection(HashSet::from([x]));
     }
 
+    /// Number of allowed values, or `None` if unconstrained
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            Self::Any => None,
+            Self::Set(s) => Some(s.len()),
+        }
+    }
+
+    /// The only allowed value, if there is exactly one
+    pub fn as_single(&self) -> Option<&T> {
+        match self {
+            Self::Set(s) if s.len() == 1 => s.iter().next(),
+            _ => None,
+        }
+    }
+
     /// NOTE: we assume the represented set is nonempty here!
     pub fn is_empty(&self) -> bool {
         match self {
@@ -33,3 +50,41 @@ impl<T: Clone + Hash + Eq> Constraint<T> {
         }
     }
 }
+
+/// Prints set elements in a deterministic order (unlike `HashSet`'s own `Debug`).
+/// Elements are sorted by their `Debug` representation, shortest first so that e.g. `NodeId(2)`
+/// comes before `NodeId(10)`.
+impl<T: Hash + Eq + Debug> Debug for Constraint<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => write!(f, "Any"),
+            Self::Set(s) => {
+                let mut elements: Vec<String> = s.iter().map(|x| format!("{x:?}")).collect();
+                elements.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+
+                write!(f, "Set({{{}}})", elements.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use open_hypergraphs::lax::NodeId;
+
+    #[test]
+    fn test_debug_is_sorted() {
+        let constraint = Constraint::Set(HashSet::from([10, 2, 0, 1].map(NodeId)));
+        assert_eq!(
+            format!("{constraint:?}"),
+            "Set({NodeId(0), NodeId(1), NodeId(2), NodeId(10)})"
+        );
+
+        assert_eq!(format!("{:?}", Constraint::<NodeId>::Any), "Any");
+        assert_eq!(
+            format!("{:?}", Constraint::<NodeId>::Set(HashSet::new())),
+            "Set({})"
+        );
+    }
+}
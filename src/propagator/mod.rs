@@ -1,6 +1,7 @@
 /// Constraints on nodes and edges
 pub mod constraint;
 
-/// "propagator" algorithm (WIP) for finding isomorphisms in non-monogamous connected open
-/// hypergraphs
+/// "propagator" algorithm for finding isomorphisms of open hypergraphs (including
+/// non-monogamous ones) which are determined by constraint propagation from the interfaces
+#[allow(clippy::module_inception)]
 pub mod propagator;
@@ -4,147 +4,327 @@ use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 
 use super::constraint::*;
-use super::types::Isomorphism;
+use crate::{Isomorphism, Permutation};
 
 // Basic idea: we "execute" the input hypergraph as a "constraint propagator".
 // Each edge is thought of as an operation which reads the current constraints on its local
 // environments, and further constrains them by looking at the open hypergraph g.
-//
-// NOTE: TODO: this is an unfinished WIP
-//
-// // next edge decided by nodes which changed last iteration
-// while let f_edge_id = get_next_edge() {
-//
-//   // This is a custom "update rule" for each edge.
-//   // We should have access to know which nodes changed, and also iterate
-//   // through them in a chosen order.
-//
-//   // POSSIBILITY:
-//   //  → The combined set of source/target nodes in g implied by f *constrains*
-//   //    the set of edges that f could correspond to in g.
-//   //  → We get this set of edges, E
-//   //  → Each e_g ∈ E implies a set of *constraints*
-//
-//   // now iterate through nodes in some priority order(?)
-//   for (f_node, f_port) in nodes_for(f_edge_id) {
-//     let g_nodes = g_index.get_sources(g_node, f_edge_label, f_edge_port);
-//
-//     // Intersect constraints of f node with all those nodes in g of the same kind
-//     *constraints[f_node] *= g_nodes;
-//   }
-// }
 
 ////////////////////////////////////////////////////////////////////////////////
 // isomorphism for fully-connected open hypergraphs by constraint propagation
 
+/// Errors raised by the propagator
+#[derive(Debug, PartialEq)]
+pub enum PropagatorError {
+    /// Constraints did not reach a fixpoint within the allowed number of iterations
+    IterationLimit,
+}
+
 /// Find an isomorphism of open hypergraphs
 /// Approach:
-///     - Associate a HashSet<usize> to each node in f, representing the possible nodes in g it
-///       could correspond to
-///     - Initialize this to all nodes for g, but singleton sets for interfaces
+///     - Associate a [`Constraint`] to each node (resp. edge) in f, representing the possible
+///       nodes (resp. edges) in g it could correspond to
+///     - Initialize this to `Constraint::Any`, but singleton sets for interfaces
 ///     - Propagate constraints: each operation does a 'local update'
+///     - Once nothing changes, read off the isomorphism if every node and edge has a single
+///       candidate. There is no branching yet, so this gives `None` otherwise.
 pub fn find_iso<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
     f: &OpenHypergraph<O, A>,
     g: &OpenHypergraph<O, A>,
 ) -> Option<Isomorphism> {
+    find_iso_bounded(f, g, usize::MAX).ok().flatten()
+}
+
+/// Like [`find_iso`], but give up with [`PropagatorError::IterationLimit`] if constraints have not
+/// reached a fixpoint after `max_iterations` passes over the edges of `f`.
+///
+/// Every pass except the last strictly shrinks at least one constraint, and the constraint on each
+/// node (resp. edge) can shrink at most `|g nodes| + 1` (resp. `|g edges| + 1`) times, so
+/// convergence takes at most `n * (n + 1) + e * (e + 1) + 1` passes for `n` nodes and `e` edges.
+pub fn find_iso_bounded<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    max_iterations: usize,
+) -> Result<Option<Isomorphism>, PropagatorError> {
     // Run fast nogood checks
-    crate::nogood::nogood(f, g)?;
+    if crate::nogood::check(f, g).is_err() {
+        return Ok(None);
+    }
 
-    // Node state is a set of constraints where:
-    //  None           => completely unconstrained
-    //  HashSet<usize> => must be in set
+    // Each node (resp. edge) of f has a constraint on the g nodes (resp. edges) it may map to:
+    //  Constraint::Any    => completely unconstrained
+    //  Constraint::Set(s) => must be in s
     let n = f.hypergraph.nodes.len();
     let e = f.hypergraph.edges.len();
 
     // Create some fast lookup information
-    let index = EdgeAdjacencyIndex::new(f);
+    let index = EdgeAdjacencyIndex::new(g);
+    let incidence = Incidence::new(f);
 
     let mut nodes: Vec<Constraint<NodeId>> = vec![Constraint::Any; n];
     let mut edges: Vec<Constraint<EdgeId>> = vec![Constraint::Any; e];
 
     // Initialize known information (interfaces!)
-    for i in 0..f.sources.len() {
-        nodes[i] = Constraint::single(g.sources[i]);
+    let interfaces = f.sources.iter().zip(&g.sources);
+    for (f_node, g_node) in interfaces.chain(f.targets.iter().zip(&g.targets)) {
+        nodes[f_node.0].intersect_one(*g_node);
     }
-    for i in 0..f.targets.len() {
-        nodes[i] = Constraint::single(g.targets[i]);
+    for (f_node, constraint) in nodes.iter().enumerate() {
+        if let Some(g_node) = constraint.as_single() {
+            let f_node = NodeId(f_node);
+            propagate_singleton(f, g, &index, &incidence, &mut edges, f_node, *g_node);
+        }
     }
 
+    let mut iterations = 0;
     let mut updated = true;
     while updated {
+        if iterations == max_iterations {
+            return Err(PropagatorError::IterationLimit);
+        }
+        iterations += 1;
         updated = false;
 
         // PERFORMANCE: only visit those edges which updated a node in their local neighbourhood
-        for edge_id in 0..edges.len() {
-            let Hyperedge { sources, targets } = &f.hypergraph.adjacency[edge_id];
-            let edge_label = &f.hypergraph.edges[edge_id];
+        // Each f edge must be one of the g edges compatible with the constraints on its nodes
+        for (edge_id, edge_label, sources, targets) in iter_edges(f, &nodes) {
+            let possible_edges =
+                possible_edges(f, g, &index, edge_id, edge_label, &sources, &targets);
+            if let Some(possible_edges) = possible_edges {
+                updated |= shrink(&mut edges[edge_id.0], possible_edges);
+            }
+        }
 
-            // For each source node of edge_id,
-            let mut possible_edges: HashSet<EdgeId> = sources
-                .iter()
-                .enumerate()
-                // TODO: map to all possible g nodes, as constrained by
-                .map(|(i, s)| index.get_source(s, edge_label, i))
-                .flatten()
-                .collect::<HashSet<_>>();
-
-            // Extend with targets
-            possible_edges.extend(
-                targets
-                    .iter()
-                    .enumerate()
-                    .map(|(i, s)| index.get_target(s, edge_label, i))
-                    .flatten(),
-            );
-
-            // Set possible edges
-            edges[edge_id].intersection(possible_edges);
-
-            // For each possible edge,
+        // An edge with no possible g edges can't be matched at all
+        if edges.iter().any(Constraint::is_empty) {
+            return Ok(None);
         }
 
-        /*
-        for (edge_id, edge_label, sources, targets) in iter_edges(f, &nodes) {
-            // For each source node, figure out the "implied targets" in g
-            for (source_id, _, g_nodes) in sources {
-                // Look up all g edges having source_id as a source node
-                for g_edge in has_source(g, source_id) {
-                    if same_signature(f, g, f_edge_id, g_edge_id) {
-                        candidates.push(todo!());
+        // Each node of an f edge must be at the same port of one of that edge's possible g edges.
+        // Decided edges say the most about their nodes, so they go first, then the undecided ones
+        // most constrained first. Each edge shrinks the constraints on its nodes, and so on the
+        // edges around them, so the next edge is picked afresh each time from `pending`: a copy of
+        // the edge constraints, kept up to date, in which the edges already visited are emptied.
+        let mut pending = edges.clone();
+        loop {
+            let decided = pending.iter().position(|c| c.size() == Some(1));
+            let Some(edge_id) = decided.or_else(|| next_edge_to_process(&pending)) else {
+                break;
+            };
+            pending[edge_id] = Constraint::Set(HashSet::new());
+            let Constraint::Set(g_edges) = &edges[edge_id] else {
+                continue;
+            };
+            let g_edges = g_edges.clone();
+
+            for f_node in constrain_ports(f, g, &mut nodes, edge_id, &g_edges) {
+                updated = true;
+                if let Some(g_node) = nodes[f_node.0].as_single() {
+                    let g_node = *g_node;
+                    propagate_singleton(f, g, &index, &incidence, &mut edges, f_node, g_node);
+
+                    let incident = incidence.sources[f_node.0].iter();
+                    for &(edge_id, _) in incident.chain(&incidence.targets[f_node.0]) {
+                        if edges[edge_id.0].is_empty() {
+                            return Ok(None);
+                        }
+                        if !pending[edge_id.0].is_empty() {
+                            pending[edge_id.0] = edges[edge_id.0].clone();
+                        }
                     }
                 }
             }
+        }
+    }
 
-            for (target_id, _, g_nodes) in targets {
-                todo!()
-            }
+    Ok(extract_isomorphism(f, g, &nodes, &edges))
+}
+
+/// The most constrained undecided edge: the one with the fewest (but more than one) possible g
+/// edges, or failing that an unconstrained one.
+/// Returns `None` if every edge is decided (or has no possibilities left).
+pub fn next_edge_to_process(edges: &[Constraint<EdgeId>]) -> Option<usize> {
+    edges
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| match c.size() {
+            Some(0 | 1) => None,
+            size => Some((size.unwrap_or(usize::MAX), i)),
+        })
+        .min()
+        .map(|(_, i)| i)
+}
 
-            updated = true;
+/// Intersect the constraint on each node of f's `edge_id` with the nodes at the same port of the
+/// possible `g_edges`, returning the nodes whose constraints shrank.
+fn constrain_ports<O, A>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    nodes: &mut [Constraint<NodeId>],
+    edge_id: usize,
+    g_edges: &HashSet<EdgeId>,
+) -> Vec<NodeId> {
+    let Hyperedge { sources, targets } = &f.hypergraph.adjacency[edge_id];
+
+    let ports = sources
+        .iter()
+        .enumerate()
+        .map(|(port, f_node)| (f_node, port, true))
+        .chain(
+            targets
+                .iter()
+                .enumerate()
+                .map(|(port, f_node)| (f_node, port, false)),
+        );
+
+    let mut shrunk = vec![];
+    for (f_node, port, is_source) in ports {
+        let possible_nodes = g_edges
+            .iter()
+            .map(|g_edge| {
+                let g_adjacency = &g.hypergraph.adjacency[g_edge.0];
+                if is_source {
+                    g_adjacency.sources[port]
+                } else {
+                    g_adjacency.targets[port]
+                }
+            })
+            .collect();
+
+        if shrink(&mut nodes[f_node.0], possible_nodes) {
+            shrunk.push(*f_node);
         }
-        */
     }
+    shrunk
+}
+
+/// Intersect a constraint with a set, returning true if this removed any values
+fn shrink<T: Clone + Hash + Eq>(constraint: &mut Constraint<T>, s: HashSet<T>) -> bool {
+    let before = constraint.size();
+    constraint.intersection(s);
+    constraint.size() != before
+}
+
+/// The g edges which f's `edge_id` could correspond to given the constraints on its `sources` and
+/// `targets`: those with the same label and arity/coarity having, at each port, one of the g nodes
+/// allowed there.
+/// Returns `None` if none of the edge's nodes are constrained.
+fn possible_edges<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    index: &EdgeAdjacencyIndex<A>,
+    edge_id: EdgeId,
+    edge_label: &A,
+    sources: &[NodeInfo<O, Constraint<NodeId>>],
+    targets: &[NodeInfo<O, Constraint<NodeId>>],
+) -> Option<HashSet<EdgeId>> {
+    let mut result: Option<HashSet<EdgeId>> = None;
+    for (is_source, nodes) in [(true, sources), (false, targets)] {
+        for (port, (_, _, constraint)) in nodes.iter().enumerate() {
+            let Constraint::Set(g_nodes) = constraint else {
+                continue;
+            };
+            let possible: HashSet<EdgeId> = g_nodes
+                .iter()
+                .flat_map(|g_node| {
+                    if is_source {
+                        index.get_source(g_node, edge_label, port)
+                    } else {
+                        index.get_target(g_node, edge_label, port)
+                    }
+                })
+                .collect();
+            result = Some(match result {
+                None => possible,
+                Some(r) => r.intersection(&possible).copied().collect(),
+            });
+        }
+    }
+
+    result.map(|r| {
+        r.into_iter()
+            .filter(|g_edge| same_arity(f, g, edge_id, *g_edge))
+            .collect()
+    })
+}
+
+/// Unit propagation: once `f_node` can only correspond to `g_node`, every f edge adjacent to
+/// `f_node` can only correspond to a g edge with the same label having `g_node` at the same port.
+fn propagate_singleton<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    index: &EdgeAdjacencyIndex<A>,
+    incidence: &Incidence,
+    edges: &mut [Constraint<EdgeId>],
+    f_node: NodeId,
+    g_node: NodeId,
+) {
+    for &(edge_id, port) in &incidence.sources[f_node.0] {
+        let edge_label = &f.hypergraph.edges[edge_id.0];
+        let compatible = index.get_source(&g_node, edge_label, port);
+        let compatible = compatible
+            .into_iter()
+            .filter(|g_edge| same_arity(f, g, edge_id, *g_edge));
+        edges[edge_id.0].intersection(compatible.collect());
+    }
+    for &(edge_id, port) in &incidence.targets[f_node.0] {
+        let edge_label = &f.hypergraph.edges[edge_id.0];
+        let compatible = index.get_target(&g_node, edge_label, port);
+        let compatible = compatible
+            .into_iter()
+            .filter(|g_edge| same_arity(f, g, edge_id, *g_edge));
+        edges[edge_id.0].intersection(compatible.collect());
+    }
+}
 
-    // TODO: return result
-    None
+/// Whether f's `f_edge` and g's `g_edge` have the same number of sources and targets
+fn same_arity<O, A>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    f_edge: EdgeId,
+    g_edge: EdgeId,
+) -> bool {
+    let f_adjacency = &f.hypergraph.adjacency[f_edge.0];
+    let g_adjacency = &g.hypergraph.adjacency[g_edge.0];
+    f_adjacency.sources.len() == g_adjacency.sources.len()
+        && f_adjacency.targets.len() == g_adjacency.targets.len()
 }
 
+/// Read off an isomorphism once every node and edge is constrained to a single value
+fn extract_isomorphism<O: Eq, A: Eq>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    nodes: &[Constraint<NodeId>],
+    edges: &[Constraint<EdgeId>],
+) -> Option<Isomorphism> {
+    let nodes = nodes.iter().map(|c| c.as_single().map(|x| x.0));
+    let edges = edges.iter().map(|c| c.as_single().map(|x| x.0));
+    let isomorphism = Isomorphism {
+        nodes: Permutation::new(nodes.collect::<Option<Vec<_>>>()?)?,
+        edges: Permutation::new(edges.collect::<Option<Vec<_>>>()?)?,
+    };
+    isomorphism.validate(f, g).is_ok().then_some(isomorphism)
+}
+
+/// `(id, label, state)` of a node
+type NodeInfo<'a, O, T> = (NodeId, &'a O, &'a T);
+/// Edge id, edge label, and `(id, label, state)` of each source and target node
+type EdgeInfo<'a, O, A, T> = (
+    EdgeId,
+    &'a A,
+    Vec<NodeInfo<'a, O, T>>,
+    Vec<NodeInfo<'a, O, T>>,
+);
+
 /// Iterate through each edge, collecting associated information:
 ///     - Edge id
 ///     - Edge label
 ///     - Source node IDs, types, and values
 ///     - Target node IDs, types, and values
-#[allow(dead_code)]
 fn iter_edges<'a, O, A, T>(
     f: &'a OpenHypergraph<O, A>,
-    s: &'a Vec<T>,
-) -> impl Iterator<
-    Item = (
-        EdgeId,
-        &'a A,
-        Vec<(NodeId, &'a O, &'a T)>,
-        Vec<(NodeId, &'a O, &'a T)>,
-    ),
-> + 'a {
+    s: &'a [T],
+) -> impl Iterator<Item = EdgeInfo<'a, O, A, T>> + 'a {
     assert_eq!(
         s.len(),
         f.hypergraph.nodes.len(),
@@ -171,37 +351,170 @@ fn iter_edges<'a, O, A, T>(
 ////////////////////////////////////////////////////////////////////////////////
 // Faster lookup information
 
-/// Index datastructure for looking up which edges a node is a source or target of.
-#[allow(dead_code)]
-struct EdgeAdjacencyIndex<'a, O, A> {
-    f: &'a OpenHypergraph<O, A>,
-    // Key/value pair `(node, edge_label, port) ⇒ edge_id` exists when
-    // `f.hypergraph.adjacency[node].sources[port] == node`
+/// Index datastructure for looking up which edges of `g` a node is a source or target of.
+struct EdgeAdjacencyIndex<A> {
+    // Key/value pair `(node, edge_label, port) ⇒ edge_ids` exists when, for each `edge_id`,
+    // `g.hypergraph.adjacency[edge_id].sources[port] == node`
     // and
-    // `f.hypergraph.edges[edge_id] == edge_label`
-    source_node_adjacency: HashMap<(NodeId, A, usize), EdgeId>,
+    // `g.hypergraph.edges[edge_id] == edge_label`
+    source_node_adjacency: HashMap<(NodeId, A, usize), Vec<EdgeId>>,
     // Same, but for targets.
-    target_node_adjacency: HashMap<(NodeId, A, usize), EdgeId>,
+    target_node_adjacency: HashMap<(NodeId, A, usize), Vec<EdgeId>>,
 }
 
-impl<'a, O, A> EdgeAdjacencyIndex<'a, O, A> {
-    fn new(f: &'a OpenHypergraph<O, A>) -> Self {
-        // TODO: create these hashmaps correctly
-        let source_node_adjacency = HashMap::new();
-        let target_node_adjacency = HashMap::new();
+impl<A: Clone + Hash + Eq> EdgeAdjacencyIndex<A> {
+    fn new<O>(g: &OpenHypergraph<O, A>) -> Self {
+        let mut source_node_adjacency: HashMap<_, Vec<EdgeId>> = HashMap::new();
+        let mut target_node_adjacency: HashMap<_, Vec<EdgeId>> = HashMap::new();
+
+        let edges = g.hypergraph.edges.iter().zip(&g.hypergraph.adjacency);
+        for (edge_id, (edge_label, adjacency)) in edges.enumerate() {
+            for (port, &node_id) in adjacency.sources.iter().enumerate() {
+                let key = (node_id, edge_label.clone(), port);
+                source_node_adjacency
+                    .entry(key)
+                    .or_default()
+                    .push(EdgeId(edge_id));
+            }
+            for (port, &node_id) in adjacency.targets.iter().enumerate() {
+                let key = (node_id, edge_label.clone(), port);
+                target_node_adjacency
+                    .entry(key)
+                    .or_default()
+                    .push(EdgeId(edge_id));
+            }
+        }
 
         EdgeAdjacencyIndex {
-            f,
             source_node_adjacency,
             target_node_adjacency,
         }
     }
 
-    fn get_source(&self, _node_id: &NodeId, _edge_label: &A, _position: usize) -> Vec<EdgeId> {
-        todo!("implement EdgeAdjacencyIndex::get_source")
+    /// Edges labelled `edge_label` having `node_id` as their source at `position`
+    fn get_source(&self, node_id: &NodeId, edge_label: &A, position: usize) -> Vec<EdgeId> {
+        let key = (*node_id, edge_label.clone(), position);
+        self.source_node_adjacency
+            .get(&key)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Edges labelled `edge_label` having `node_id` as their target at `position`
+    fn get_target(&self, node_id: &NodeId, edge_label: &A, position: usize) -> Vec<EdgeId> {
+        let key = (*node_id, edge_label.clone(), position);
+        self.target_node_adjacency
+            .get(&key)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// For each node, the `(edge, port)` pairs of which it is a source (resp. target)
+struct Incidence {
+    sources: Vec<Vec<(EdgeId, usize)>>,
+    targets: Vec<Vec<(EdgeId, usize)>>,
+}
+
+impl Incidence {
+    fn new<O, A>(f: &OpenHypergraph<O, A>) -> Self {
+        let n = f.hypergraph.nodes.len();
+        let mut sources = vec![vec![]; n];
+        let mut targets = vec![vec![]; n];
+
+        for (edge_id, adjacency) in f.hypergraph.adjacency.iter().enumerate() {
+            for (port, node_id) in adjacency.sources.iter().enumerate() {
+                sources[node_id.0].push((EdgeId(edge_id), port));
+            }
+            for (port, node_id) in adjacency.targets.iter().enumerate() {
+                targets[node_id.0].push((EdgeId(edge_id), port));
+            }
+        }
+
+        Incidence { sources, targets }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Input `x` is copied into a `negate` and a `cast`, each of whose outputs is a target
+    fn copy_then_negate_and_cast() -> OpenHypergraph<usize, char> {
+        let mut f = OpenHypergraph::empty();
+        let x = f.new_node(0);
+        let y = f.new_node(0);
+        let z = f.new_node(1);
+        f.new_edge('n', (vec![x], vec![y]));
+        f.new_edge('c', (vec![x], vec![z]));
+        f.sources = vec![x];
+        f.targets = vec![y, z];
+        f
     }
 
-    fn get_target(&self, _node_id: &NodeId, _edge_label: &A, _position: usize) -> Vec<EdgeId> {
-        todo!("implement EdgeAdjacencyIndex::get_target")
+    #[test]
+    fn test_find_iso_non_monogamous() {
+        let f = copy_then_negate_and_cast();
+        let i = Isomorphism::identity(3, 2);
+        assert_eq!(find_iso(&f, &f), Some(i));
+
+        // swap the order of the edges
+        let iso = Isomorphism {
+            nodes: Permutation::identity(3),
+            edges: Permutation::new(vec![1, 0]).unwrap(),
+        };
+        let g = iso.apply(&f);
+        assert_eq!(find_iso(&f, &g), Some(iso));
+    }
+
+    #[test]
+    fn test_singleton_propagation() {
+        // Pinning the source of f to the source of g leaves only one candidate for each edge
+        let f = copy_then_negate_and_cast();
+        let index = EdgeAdjacencyIndex::new(&f);
+        let incidence = Incidence::new(&f);
+        let mut edges = vec![Constraint::Any; 2];
+        propagate_singleton(&f, &f, &index, &incidence, &mut edges, NodeId(0), NodeId(0));
+        assert_eq!(edges[0], Constraint::single(EdgeId(0)));
+        assert_eq!(edges[1], Constraint::single(EdgeId(1)));
+    }
+
+    #[test]
+    fn test_next_edge_to_process() {
+        let edges = vec![
+            Constraint::single(EdgeId(0)),
+            Constraint::Any,
+            Constraint::Set(HashSet::from([EdgeId(0), EdgeId(1), EdgeId(2)])),
+            Constraint::Set(HashSet::new()),
+            Constraint::Set(HashSet::from([EdgeId(1), EdgeId(2)])),
+        ];
+        assert_eq!(next_edge_to_process(&edges), Some(4));
+        assert_eq!(next_edge_to_process(&edges[..4]), Some(2));
+        assert_eq!(next_edge_to_process(&edges[..2]), Some(1));
+        assert_eq!(next_edge_to_process(&edges[..1]), None);
+    }
+
+    #[test]
+    fn test_find_iso_not_isomorphic() {
+        let f = copy_then_negate_and_cast();
+        // same labels and interface types, but `negate` and `cast` produce each other's outputs
+        let mut g = f.clone();
+        g.hypergraph.edges.swap(0, 1);
+        assert_eq!(find_iso(&f, &g), None);
+
+        // an edge is left with no possible g edges in the first pass, so no more are needed
+        assert_eq!(find_iso_bounded(&f, &g, 1), Ok(None));
+    }
+
+    #[test]
+    fn test_find_iso_bounded_iteration_limit() {
+        // No edges, so constraints converge after a single pass
+        let f = OpenHypergraph::<usize, usize>::identity(vec![0, 1]);
+
+        assert_eq!(
+            find_iso_bounded(&f, &f, 0),
+            Err(PropagatorError::IterationLimit)
+        );
+        assert!(find_iso_bounded(&f, &f, 1).is_ok());
     }
 }
@@ -0,0 +1,83 @@
+//! [`Serialize`] and [`Deserialize`] for [`Permutation`] as a plain list of values.
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::Permutation;
+
+impl Serialize for Permutation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+/// Fails with a description of the first out-of-range or repeated value
+impl<'de> Deserialize<'de> for Permutation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<usize>::deserialize(deserializer)?;
+
+        let n = values.len();
+        let mut seen = vec![false; n];
+        for &value in &values {
+            if value >= n {
+                return Err(de::Error::custom(format!(
+                    "value {value} out of range for a permutation of length {n}"
+                )));
+            }
+            if seen[value] {
+                return Err(de::Error::custom(format!(
+                    "value {value} appears more than once"
+                )));
+            }
+            seen[value] = true;
+        }
+
+        Ok(Permutation::new(values).expect("checked above"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Isomorphism, Permutation};
+
+    #[test]
+    fn test_round_trip() {
+        let p = Permutation::new([2, 0, 1]).unwrap();
+        let json = serde_json::to_string(&p).unwrap();
+        assert_eq!(json, "[2,0,1]");
+        assert_eq!(serde_json::from_str::<Permutation>(&json).unwrap(), p);
+
+        let iso = Isomorphism {
+            nodes: p,
+            edges: Permutation::identity(2),
+        };
+        let json = serde_json::to_string(&iso).unwrap();
+        assert_eq!(json, r#"{"nodes":[2,0,1],"edges":[0,1]}"#);
+        assert!(serde_json::from_str::<Isomorphism>(&json).unwrap() == iso);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = serde_json::from_str::<Permutation>("[0, 3, 1]").unwrap_err();
+        assert!(error.to_string().contains("value 3 out of range"));
+
+        let error = serde_json::from_str::<Permutation>("[0, 1, 1]").unwrap_err();
+        assert!(error.to_string().contains("value 1 appears more than once"));
+
+        let json = r#"{"nodes":[0,0],"edges":[]}"#;
+        assert!(serde_json::from_str::<Isomorphism>(json).is_err());
+    }
+
+    #[test]
+    fn test_invalid_isomorphism() {
+        let parse = |json| {
+            serde_json::from_str::<Isomorphism>(json)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(parse(r#"{"nodes":[1,0],"edges":[5]}"#).contains("value 5 out of range"));
+        assert!(parse(r#"{"nodes":[1,0]}"#).contains("missing field `edges`"));
+        assert!(parse(r#"{"nodes":"(0 1)","edges":[]}"#).contains("invalid type"));
+        assert!(parse(r#"[[1,0],[0]"#).contains("EOF"));
+    }
+}
@@ -1,28 +1,69 @@
-//! Michael McLeod's traversal-based algorithm for isomorphism finding in monogamous connected
-//! hypergraphs
-use open_hypergraphs::lax::{EdgeId, NodeId, OpenHypergraph};
-use std::collections::HashMap;
+//! Michael McLeod's traversal-based algorithm for isomorphism finding in monogamous hypergraphs
+use open_hypergraphs::lax::{EdgeId, Hypergraph, NodeId, OpenHypergraph};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::Hash;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
 
+use crate::isomorphism::{PartialIsomorphism, ValidationError};
+use crate::nogood::NogoodReason;
 use crate::{Isomorphism, Permutation};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Error {
     /// A nogood check failed
-    Nogood,
-    NonMonogamous(NodeId),
+    Nogood {
+        reason: NogoodReason,
+    },
+    /// The inputs are outside what this algorithm can handle, but may still be isomorphic: try
+    /// [`crate::propagator::propagator::find_iso`] instead.
+    AlgorithmNotApplicable {
+        reason: NotApplicableReason,
+    },
     Unsatisfiable(NodeId),
+    /// Some part of f not reachable from its interfaces could not be paired with any part of g
+    UnmatchedComponent,
     // InvalidMatch(node_f, node_g) means node_f was supposed to correspond to node_g but a
     // constraint was not satisfied
     InvalidNodeMatch(NodeId, NodeId),
     InvalidEdgeMatch(EdgeId, EdgeId),
 
-    // f node had no corresponding g node.
-    UnpairedNode(NodeId),
-    UnpairedEdge(EdgeId),
-
     InvalidNodePermutation,
     InvalidEdgePermutation,
+
+    /// A given isomorphism failed [`Isomorphism::validate`]
+    Validation(ValidationError),
+    /// The search abandoned more branches than [`SearchConfig::max_backtracks`] allows
+    BacktrackLimit,
+    /// Two interface positions hold the same node in one of f, g but different nodes in the
+    /// other; this is the node of f at the first such position
+    DuplicateInterfaceNode(NodeId),
+}
+
+/// Why the traversal cannot decide whether two hypergraphs are isomorphic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotApplicableReason {
+    /// The node is the source (resp. target) of more than one edge port, or is an interface
+    /// source (resp. target) which is also the target (resp. source) of an edge
+    NonMonogamous(NodeId),
+}
+
+impl From<NotApplicableReason> for Error {
+    fn from(reason: NotApplicableReason) -> Self {
+        Error::AlgorithmNotApplicable { reason }
+    }
+}
+
+impl From<NogoodReason> for Error {
+    fn from(reason: NogoodReason) -> Self {
+        Error::Nogood { reason }
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(error: ValidationError) -> Self {
+        Error::Validation(error)
+    }
 }
 
 /// Pseudocode:
@@ -46,13 +87,232 @@ pub enum Error {
 ///   }
 /// }
 /// ```
+///
+/// Components of `f` with no interface nodes are never reached this way. Once the stack is empty,
+/// each is paired with a component of `g` by choosing an image for one of its nodes and
+/// traversing again, backtracking if that fails. A wrong choice is usually noticed as soon as the
+/// traversal meets a mismatched label, but with `k` components of the same shape that are not
+/// matched by anything in `g`, all `k!` pairings may be tried before failing.
 pub fn find_isomorphism<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
     f: &OpenHypergraph<O, A>,
     g: &OpenHypergraph<O, A>,
 ) -> Result<Isomorphism, Error> {
-    let state = SearchState::new(f, g)?;
+    // Run fast nogood checks
+    crate::nogood::check(f, g)?;
+    find_isomorphism_unchecked(f, g)
+}
+
+/// Like [`find_isomorphism`], but skip the [`crate::nogood::check`].
+/// Only call when you know the nogood passes, e.g. because you already ran it yourself: otherwise
+/// the traversal may do a lot of work before failing (with a less informative error).
+pub fn find_isomorphism_unchecked<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+) -> Result<Isomorphism, Error> {
+    search_from(&SearchState::new(f, g)?)
+}
+
+/// Like [`find_isomorphism`], but also start from the known correspondences in `seed`, each of
+/// which must hold in the result.
+/// This prunes the search for components not reachable from the interfaces.
+pub fn find_isomorphism_from_seed<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    seed: HashMap<NodeId, NodeId>,
+) -> Result<Isomorphism, Error> {
+    crate::nogood::check(f, g)?;
+    let partial = partial_of_seed(f, &seed)?;
+    search_from(&SearchState::new_seeded(f, g, Some(&partial))?)
+}
+
+fn partial_of_seed<O, A>(
+    f: &OpenHypergraph<O, A>,
+    seed: &HashMap<NodeId, NodeId>,
+) -> Result<PartialIsomorphism, Error> {
+    let mut partial = PartialIsomorphism::empty(f.hypergraph.nodes.len(), f.hypergraph.edges.len());
+    for (&f_node, &g_node) in seed {
+        let Some(image) = partial.nodes.get_mut(f_node.0) else {
+            return Err(Error::InvalidNodeMatch(f_node, g_node));
+        };
+        *image = Some(g_node.0);
+    }
+    Ok(partial)
+}
+
+/// The order in which the traversal processes pending pairs of nodes, and picks an f node to
+/// branch on when it gets stuck.
+///
+/// `Dfs` and `Bfs` differ only in which pending pair is processed next; both branch on the same
+/// node. Only `MostConstrainedFirst` changes where the search branches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TraversalOrder {
+    /// Process the most recently found pair first, and branch on the first unvisited node
+    #[default]
+    Dfs,
+    /// Process pairs in the order they were found, and branch on the first unvisited node
+    Bfs,
+    /// Like `Dfs`, but branch on the unvisited node with the fewest candidate images
+    MostConstrainedFirst,
+}
+
+/// Options for [`find_isomorphism_with_config`]. The default is the search of
+/// [`find_isomorphism`].
+#[derive(Debug, Clone, Default)]
+pub struct SearchConfig {
+    pub order: TraversalOrder,
+    /// Give up with [`Error::BacktrackLimit`] after abandoning this many branches of the search
+    pub max_backtracks: Option<usize>,
+    /// Known correspondences to start from, as in [`find_isomorphism_from_seed`]
+    pub seed_hint: Option<HashMap<NodeId, NodeId>>,
+}
+
+/// Like [`find_isomorphism`], but searching as described by `config`
+pub fn find_isomorphism_with_config<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    config: &SearchConfig,
+) -> Result<Isomorphism, Error> {
+    crate::nogood::check(f, g)?;
+    let seed = match &config.seed_hint {
+        Some(seed) => Some(partial_of_seed(f, seed)?),
+        None => None,
+    };
+
+    let mut state = SearchState::new_seeded(f, g, seed.as_ref())?;
+    state.order = config.order;
+    let mut limits = Limits {
+        backtracks: config.max_backtracks,
+        ..Limits::default()
+    };
+    match state.find_isomorphism_within(&mut limits)? {
+        Some((node_mapping, edge_mapping)) => into_isomorphism(node_mapping, edge_mapping),
+        None => Err(Error::BacktrackLimit),
+    }
+}
+
+/// Check that `iso` is an isomorphism `f → g`, saying which check failed if not; see
+/// [`Isomorphism::validate`].
+pub fn verify_isomorphism<O: Eq, A: Eq>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    iso: &Isomorphism,
+) -> Result<(), Error> {
+    Ok(iso.validate(f, g)?)
+}
+
+/// Like [`find_isomorphism`], but ignoring the labels of nodes and edges: an isomorphism of the
+/// underlying shapes of `f` and `g`.
+pub fn find_structural_isomorphism<O, A>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+) -> Result<Isomorphism, Error> {
+    find_isomorphism(&shape(f), &shape(g))
+}
+
+/// `f` with every label replaced by `()`
+fn shape<O, A>(f: &OpenHypergraph<O, A>) -> OpenHypergraph<(), ()> {
+    OpenHypergraph {
+        sources: f.sources.clone(),
+        targets: f.targets.clone(),
+        hypergraph: Hypergraph {
+            nodes: vec![(); f.hypergraph.nodes.len()],
+            edges: vec![(); f.hypergraph.edges.len()],
+            adjacency: f.hypergraph.adjacency.clone(),
+            quotient: f.hypergraph.quotient.clone(),
+        },
+    }
+}
+
+/// Every isomorphism `f → g`, found lazily by the same backtracking search as
+/// [`SearchState::find_all_isomorphisms`].
+///
+/// Pending branches are kept on an explicit stack, so each isomorphism is produced exactly once
+/// without restarting the search. If the inputs fail the nogood check or the traversal doesn't
+/// apply to them, the only item is that `Err`.
+pub fn find_all_isomorphisms<'a, O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+    f: &'a OpenHypergraph<O, A>,
+    g: &'a OpenHypergraph<O, A>,
+) -> impl Iterator<Item = Result<Isomorphism, Error>> + 'a {
+    let start = crate::nogood::check(f, g)
+        .map_err(Error::from)
+        .and_then(|()| SearchState::new(f, g))
+        .and_then(|state| state.check_sizes().map(|()| state));
+    let (state, error, stack) = match start {
+        Ok(state) => {
+            let initial = state.initial.clone();
+            (Some(state), None, vec![initial])
+        }
+        Err(error) => (None, Some(error), vec![]),
+    };
+    Isomorphisms {
+        state,
+        error,
+        stack,
+    }
+}
+
+/// The iterator returned by [`find_all_isomorphisms`]
+struct Isomorphisms<'a, O, A> {
+    state: Option<SearchState<'a, O, A>>,
+    error: Option<Error>,
+    stack: Vec<Frame>,
+}
+
+impl<O: Eq + Clone + Hash, A: Eq + Clone + Hash> Iterator for Isomorphisms<'_, O, A> {
+    type Item = Result<Isomorphism, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+        let state = self.state.as_ref()?;
+        while let Some(frame) = self.stack.pop() {
+            match state.expand(frame) {
+                Step::Dead => {}
+                Step::Found(isomorphism) => return Some(Ok(isomorphism)),
+                // Reversed, so that branches are explored in the same order as by `search`
+                Step::Fork(branches) => self.stack.extend(branches.into_iter().rev()),
+            }
+        }
+        None
+    }
+}
+
+/// Like [`find_isomorphism`], but giving up with `Ok(None)` once `timeout` has passed.
+///
+/// The deadline is checked before each step of the backtracking search for components not
+/// reachable from the interfaces; the traversal from the interfaces themselves, which is linear
+/// in the size of `f`, always runs to completion.
+pub fn find_isomorphism_with_timeout<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+    f: &OpenHypergraph<O, A>,
+    g: &OpenHypergraph<O, A>,
+    timeout: Duration,
+) -> Result<Option<Isomorphism>, Error> {
+    crate::nogood::check(f, g)?;
+    // A timeout too large to represent is no timeout at all
+    let mut limits = Limits {
+        deadline: Instant::now().checked_add(timeout),
+        ..Limits::default()
+    };
+    match SearchState::new(f, g)?.find_isomorphism_within(&mut limits)? {
+        Some((node_mapping, edge_mapping)) => {
+            into_isomorphism(node_mapping, edge_mapping).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
+fn search_from<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
+    state: &SearchState<O, A>,
+) -> Result<Isomorphism, Error> {
     let (node_mapping, edge_mapping) = state.find_isomorphism()?;
+    into_isomorphism(node_mapping, edge_mapping)
+}
 
+fn into_isomorphism(
+    node_mapping: Vec<NodeId>,
+    edge_mapping: Vec<EdgeId>,
+) -> Result<Isomorphism, Error> {
     let nodes = Permutation::new(node_mapping.into_iter().map(|x| x.0));
     let edges = Permutation::new(edge_mapping.into_iter().map(|x| x.0));
 
@@ -62,13 +322,55 @@ pub fn find_isomorphism<O: Eq + Clone + Hash, A: Eq + Clone + Hash>(
     Ok(Isomorphism { nodes, edges })
 }
 
-/// Indexes for a pair of open hypergraphs
-struct SearchState<'a, O, A> {
+/// The image of each node and edge of f
+type Mapping = (Vec<NodeId>, Vec<EdgeId>);
+
+/// When the backtracking search should give up
+#[derive(Default)]
+struct Limits {
+    deadline: Option<Instant>,
+    /// How many more branches may be abandoned
+    backtracks: Option<usize>,
+    /// Whether more branches than that were abandoned
+    overrun: bool,
+}
+
+impl Limits {
+    fn exhausted(&self) -> bool {
+        self.overrun
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Record an abandoned branch
+    fn backtrack(&mut self) {
+        match &mut self.backtracks {
+            Some(0) => self.overrun = true,
+            Some(remaining) => *remaining -= 1,
+            None => {}
+        }
+    }
+}
+
+/// Indexes for a pair of open hypergraphs, and the pairings the search starts from.
+///
+/// The traversal can also be run by hand with [`SearchState::step`], inspecting progress with
+/// [`SearchState::result`]. Steps only make pairings forced by those already made, so the
+/// searches ([`SearchState::find_all_isomorphisms`] etc.) give the same answers from a stepped
+/// state as from a fresh one, and once a step has failed they all fail with its error.
+pub struct SearchState<'a, O, A> {
     f: &'a OpenHypergraph<O, A>,
     g: &'a OpenHypergraph<O, A>,
 
     f_index: Index,
     g_index: Index,
+
+    order: TraversalOrder,
+    initial: Frame,
+    /// The error of a failed [`SearchState::step`], which leaves `initial` part-way through a
+    /// pairing
+    failed: Option<Error>,
 }
 
 impl<'a, O: Eq + Clone + Hash, A: Eq + Clone + Hash> SearchState<'a, O, A> {
@@ -76,64 +378,384 @@ impl<'a, O: Eq + Clone + Hash, A: Eq + Clone + Hash> SearchState<'a, O, A> {
         f: &'a OpenHypergraph<O, A>,
         g: &'a OpenHypergraph<O, A>,
     ) -> Result<SearchState<'a, O, A>, Error> {
+        Self::new_seeded(f, g, None)
+    }
+
+    /// Like [`SearchState::new`], but also start from the pairings of nodes and edges in `seed`
+    /// (in addition to those of the interfaces).
+    /// This lets the traversal reach parts of `f` not reachable from its interfaces.
+    pub fn new_seeded(
+        f: &'a OpenHypergraph<O, A>,
+        g: &'a OpenHypergraph<O, A>,
+        seed: Option<&PartialIsomorphism>,
+    ) -> Result<SearchState<'a, O, A>, Error> {
+        // The search is seeded by pairing f's interface nodes with g's position-by-position, so
+        // mismatched lengths would silently drop the unpaired nodes instead of failing.
+        if f.sources.len() != g.sources.len() {
+            return Err(NogoodReason::SourceLength.into());
+        }
+        if f.targets.len() != g.targets.len() {
+            return Err(NogoodReason::TargetLength.into());
+        }
+
+        // A node may appear several times in the interfaces (e.g. in an identity), but the same
+        // positions of g's interfaces must then all hold the same node, and vice versa
+        let mut f_to_g = HashMap::new();
+        let mut g_to_f = HashMap::new();
+        let f_interface = f.sources.iter().chain(&f.targets);
+        let g_interface = g.sources.iter().chain(&g.targets);
+        for (&f_node, &g_node) in f_interface.zip(g_interface) {
+            if *f_to_g.entry(f_node).or_insert(g_node) != g_node {
+                return Err(Error::DuplicateInterfaceNode(f_node));
+            }
+            if let Some(&other) = g_to_f.get(&g_node)
+                && other != f_node
+            {
+                return Err(Error::DuplicateInterfaceNode(other));
+            }
+            g_to_f.insert(g_node, f_node);
+        }
+
         let f_index = Index::new(&f.hypergraph);
         let g_index = Index::new(&g.hypergraph);
 
+        // Verify that no node is the source (resp. target) of more than one edge port, since the
+        // traversal follows only one
+        for node in 0..f.hypergraph.nodes.len() {
+            if !f_index.is_monogamous_at(NodeId(node)) {
+                return Err(NotApplicableReason::NonMonogamous(NodeId(node)).into());
+            }
+        }
+
         // Verify that source/target nodes are not targets/sources, respectively
         // (monogamicity check!)
         for &source_node in &f.sources {
             if f_index.of_target.contains_key(&source_node) {
-                return Err(Error::NonMonogamous(source_node));
+                return Err(NotApplicableReason::NonMonogamous(source_node).into());
             }
         }
         for &target_node in &f.targets {
             if f_index.of_source.contains_key(&target_node) {
-                return Err(Error::NonMonogamous(target_node));
+                return Err(NotApplicableReason::NonMonogamous(target_node).into());
             }
         }
 
-        Ok(SearchState {
+        let mut state = SearchState {
             f,
             g,
             f_index,
             g_index,
-        })
+            order: TraversalOrder::default(),
+            initial: Frame::new(f, g),
+            failed: None,
+        };
+        if let Some(seed) = seed {
+            let mut initial = state.initial.clone();
+            state.seed(&mut initial, seed)?;
+            state.initial = initial;
+        }
+        Ok(state)
+    }
+
+    /// Add the pairings of `seed` to the frame: seeded nodes go on the stack like the interfaces,
+    /// and seeded edges are identified immediately.
+    fn seed(&self, frame: &mut Frame, seed: &PartialIsomorphism) -> Result<(), Error> {
+        if seed.nodes.len() != self.f.hypergraph.nodes.len() {
+            return Err(NogoodReason::NodeCount.into());
+        }
+        if seed.edges.len() != self.f.hypergraph.edges.len() {
+            return Err(NogoodReason::EdgeCount.into());
+        }
+
+        let mut images = HashSet::new();
+        for (f_node, g_node) in seed.nodes.iter().enumerate() {
+            let Some(g_node) = *g_node else {
+                continue;
+            };
+            let pair = (NodeId(f_node), NodeId(g_node));
+            // Out of range, or already the image of another node
+            if g_node >= self.g.hypergraph.nodes.len() || !images.insert(g_node) {
+                return Err(Error::InvalidNodeMatch(pair.0, pair.1));
+            }
+
+            // Interface nodes are already paired, which the seed must agree with
+            if frame.visited[f_node] {
+                if !frame.stack.contains(&pair) {
+                    return Err(Error::InvalidNodeMatch(pair.0, pair.1));
+                }
+                continue;
+            }
+            frame.visited[f_node] = true;
+            frame.stack.push_back(pair);
+        }
+
+        for (f_edge, g_edge) in seed.edges.iter().enumerate() {
+            let Some(g_edge) = *g_edge else {
+                continue;
+            };
+            let (f_edge, g_edge) = (EdgeId(f_edge), EdgeId(g_edge));
+            if g_edge.0 >= self.g.hypergraph.edges.len() {
+                return Err(Error::InvalidEdgeMatch(f_edge, g_edge));
+            }
+            let Frame { stack, visited, .. } = frame;
+            self.identify_edges(stack, visited, f_edge, g_edge)?;
+            frame.edge_mapping[f_edge.0] = Some(g_edge);
+        }
+
+        Ok(())
+    }
+
+    /// Process one pending pair of nodes: check they have the same label, and pair up the edges
+    /// they are sources or targets of, adding the other nodes of those edges to the pending pairs.
+    /// Returns `Ok(false)`, doing nothing, if there are no pending pairs left.
+    ///
+    /// If this returns an error, no isomorphism extends the pairings made so far. The error is
+    /// kept: every later step, and every search from this state, fails with it too (or, for
+    /// [`SearchState::find_all_isomorphisms`], finds nothing).
+    pub fn step(&mut self) -> Result<bool, Error> {
+        if let Some(error) = &self.failed {
+            return Err(error.clone());
+        }
+        let mut frame = std::mem::take(&mut self.initial);
+        let result = self.traverse_one(&mut frame);
+        self.initial = frame;
+        if let Err(error) = &result {
+            self.failed = Some(error.clone());
+        }
+        result
+    }
+
+    /// The image of each node and edge, once every one has been paired by [`SearchState::step`]
+    /// (with no error). This is not necessarily an isomorphism until there are no pending pairs
+    /// left, and it has passed [`crate::Isomorphism::validate`].
+    pub fn result(&self) -> Option<(Vec<NodeId>, Vec<EdgeId>)> {
+        let node_mapping = self
+            .initial
+            .node_mapping
+            .iter()
+            .copied()
+            .collect::<Option<_>>()?;
+        let edge_mapping = self
+            .initial
+            .edge_mapping
+            .iter()
+            .copied()
+            .collect::<Option<_>>()?;
+        Some((node_mapping, edge_mapping))
+    }
+
+    /// The index of the hypergraph `f`
+    pub fn f_index(&self) -> &Index {
+        &self.f_index
+    }
+
+    /// The index of the hypergraph `g`
+    pub fn g_index(&self) -> &Index {
+        &self.g_index
+    }
+
+    fn find_isomorphism(&self) -> Result<Mapping, Error> {
+        let found = self.find_isomorphism_within(&mut Limits::default())?;
+        Ok(found.expect("only limits stop the search without an answer"))
+    }
+
+    /// Like `find_isomorphism`, but `Ok(None)` if the search exceeds the `limits`
+    fn find_isomorphism_within(&self, limits: &mut Limits) -> Result<Option<Mapping>, Error> {
+        if let Some(error) = &self.failed {
+            return Err(error.clone());
+        }
+        self.check_sizes()?;
+
+        let mut frame = self.initial.clone();
+        self.traverse(&mut frame)?;
+
+        let node_mapping: Option<Vec<_>> = frame.node_mapping.iter().copied().collect();
+        let edge_mapping: Option<Vec<_>> = frame.edge_mapping.iter().copied().collect();
+        if let (Some(node_mapping), Some(edge_mapping)) = (node_mapping, edge_mapping) {
+            return Ok(Some((node_mapping, edge_mapping)));
+        }
+
+        // Whatever is left was not reachable from an interface, so search for a pairing of the
+        // remaining components, stopping at the first that works
+        let mut found = None;
+        let _ = self.search(frame, limits, &mut |isomorphism| {
+            found = Some(isomorphism);
+            ControlFlow::Break(())
+        });
+        match found {
+            Some(isomorphism) => Ok(Some((
+                isomorphism.nodes.iter().map(|&i| NodeId(i)).collect(),
+                isomorphism.edges.iter().map(|&i| EdgeId(i)).collect(),
+            ))),
+            None if limits.exhausted() => Ok(None),
+            None => Err(Error::UnmatchedComponent),
+        }
     }
 
-    fn find_isomorphism(&self) -> Result<(Vec<NodeId>, Vec<EdgeId>), Error> {
-        // Run fast nogood checks
-        crate::nogood::nogood(self.f, self.g).ok_or(Error::Nogood)?;
+    /// All isomorphisms `f → g`.
+    ///
+    /// Runs the traversal from the interfaces as in [`find_isomorphism`]; whenever it gets stuck
+    /// with some f nodes (or edges) not yet reached, it forks, trying each unmatched g node (or
+    /// edge) of the same label in turn. Unlike [`find_isomorphism`], it doesn't stop at the first.
+    /// For monogamous hypergraphs in which every node is reachable from the interfaces there is
+    /// never a choice, so the result has at most one element.
+    pub fn find_all_isomorphisms(&self) -> Vec<Isomorphism> {
+        let mut results = vec![];
+        if self.failed.is_none()
+            && self.check_sizes().is_ok()
+            && crate::nogood::check(self.f, self.g).is_ok()
+        {
+            let _ = self.search(
+                self.initial.clone(),
+                &mut Limits::default(),
+                &mut |isomorphism| {
+                    results.push(isomorphism);
+                    ControlFlow::Continue(())
+                },
+            );
+        }
+        results
+    }
 
+    /// Size checks run before searching
+    fn check_sizes(&self) -> Result<(), Error> {
         let f = self.f;
         let g = self.g;
 
-        let n = f.hypergraph.nodes.len();
-        let e = f.hypergraph.edges.len();
+        // Every f node and edge gets paired with a distinct g node and edge, so if g is larger
+        // some of its nodes and edges would be silently left unmatched.
+        if f.hypergraph.nodes.len() != g.hypergraph.nodes.len() {
+            return Err(NogoodReason::NodeCount.into());
+        }
+        if f.hypergraph.edges.len() != g.hypergraph.edges.len() {
+            return Err(NogoodReason::EdgeCount.into());
+        }
+        Ok(())
+    }
 
-        // The set of visited nodes, serving double duty as the assigned mapping to g.
-        // Note that we never visit a node twice.
-        let mut node_mapping: Vec<Option<NodeId>> = vec![None; n];
-        let mut edge_mapping: Vec<Option<EdgeId>> = vec![None; e];
+    /// Backtracking search from `frame` (see [`SearchState::expand`]), passing every complete and
+    /// valid mapping to `found` until it breaks. Also breaks at each step once the `limits` are
+    /// exceeded.
+    fn search(
+        &self,
+        frame: Frame,
+        limits: &mut Limits,
+        found: &mut impl FnMut(Isomorphism) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        if limits.exhausted() {
+            return ControlFlow::Break(());
+        }
+        match self.expand(frame) {
+            Step::Dead => {
+                limits.backtrack();
+                ControlFlow::Continue(())
+            }
+            Step::Found(isomorphism) => found(isomorphism),
+            Step::Fork(branches) => {
+                for branch in branches {
+                    self.search(branch, limits, found)?;
+                }
+                ControlFlow::Continue(())
+            }
+        }
+    }
 
-        // "stack" is our priority queue of unvisited f nodes.
-        // Each is paired with a single g node.
-        // Initialize to the *interfaces* of both open hypergraphs.
-        let mut stack = vec![];
-        stack.extend(f.sources.iter().copied().zip(g.sources.iter().copied()));
-        stack.extend(f.targets.iter().copied().zip(g.targets.iter().copied()));
+    /// One step of the backtracking search: traverse as far as possible from the frame's pending
+    /// pairs, then fork on the first unvisited f node (or unmatched f edge).
+    fn expand(&self, mut frame: Frame) -> Step {
+        if self.traverse(&mut frame).is_err() {
+            return Step::Dead;
+        }
 
-        // which nodes of f have been visited (either in stack, or in f_to_g)
-        // Initialize to interfaces since they're already on the stack
-        let mut visited: Vec<bool> = vec![false; n];
-        for &source_node in &f.sources {
-            visited[source_node.0] = true;
+        let g_nodes = &self.g.hypergraph.nodes;
+        let mut unvisited = (0..frame.visited.len()).filter(|&i| !frame.visited[i]);
+        // `Dfs` and `Bfs` only order the pending pairs (see `pop`), so they branch alike
+        let f_node = match self.order {
+            TraversalOrder::Dfs | TraversalOrder::Bfs => unvisited.next(),
+            TraversalOrder::MostConstrainedFirst => {
+                // An f node's candidates are the unmatched g nodes with its label, so count those
+                // once for each label rather than once for each f node
+                let mut free: HashMap<&O, usize> = HashMap::new();
+                for (g_node, label) in g_nodes.iter().enumerate() {
+                    if !frame.g_mapped[g_node] {
+                        *free.entry(label).or_default() += 1;
+                    }
+                }
+                unvisited.min_by_key(|&i| free.get(&self.f.hypergraph.nodes[i]).unwrap_or(&0))
+            }
+        };
+        if let Some(f_node) = f_node {
+            // The unmatched g nodes with the same label as the f node
+            let label = &self.f.hypergraph.nodes[f_node];
+            let candidates = (0..g_nodes.len())
+                .filter(|&g_node| !frame.g_mapped[g_node] && &g_nodes[g_node] == label);
+
+            let mut branches = vec![];
+            for g_node in candidates {
+                let mut branch = frame.clone();
+                branch.visited[f_node] = true;
+                branch.stack.push_back((NodeId(f_node), NodeId(g_node)));
+                branches.push(branch);
+            }
+            return Step::Fork(branches);
         }
-        for &target_node in &f.targets {
-            visited[target_node.0] = true;
+
+        // All nodes are visited, but edges with no nodes are never reached by traversal
+        if let Some(f_edge) = frame.edge_mapping.iter().position(|e| e.is_none()) {
+            let mapped: HashSet<EdgeId> = frame.edge_mapping.iter().flatten().copied().collect();
+            let mut branches = vec![];
+            for g_edge in 0..self.g.hypergraph.edges.len() {
+                let (f_edge, g_edge) = (EdgeId(f_edge), EdgeId(g_edge));
+                if mapped.contains(&g_edge) {
+                    continue;
+                }
+                let mut branch = frame.clone();
+                let Frame { stack, visited, .. } = &mut branch;
+                if self.identify_edges(stack, visited, f_edge, g_edge).is_ok() {
+                    branch.edge_mapping[f_edge.0] = Some(g_edge);
+                    branches.push(branch);
+                }
+            }
+            return Step::Fork(branches);
+        }
+
+        // Every node is visited, but a node can still be unmapped if it was visited by a pairing
+        // that failed
+        let nodes: Option<Vec<_>> = frame.node_mapping.iter().map(|x| Some((*x)?.0)).collect();
+        let edges: Option<Vec<_>> = frame.edge_mapping.iter().map(|x| Some((*x)?.0)).collect();
+        let (Some(nodes), Some(edges)) = (nodes, edges) else {
+            return Step::Dead;
+        };
+        if let (Some(nodes), Some(edges)) = (Permutation::new(nodes), Permutation::new(edges)) {
+            let isomorphism = Isomorphism { nodes, edges };
+            if isomorphism.validate(self.f, self.g).is_ok() {
+                return Step::Found(isomorphism);
+            }
         }
+        Step::Dead
+    }
+
+    /// Pair up nodes and edges reachable from the frame's pending pairs until the stack is empty
+    fn traverse(&self, frame: &mut Frame) -> Result<(), Error> {
+        while self.traverse_one(frame)? {}
+        Ok(())
+    }
+
+    /// Process the next of the frame's pending pairs, if there is one
+    fn traverse_one(&self, frame: &mut Frame) -> Result<bool, Error> {
+        let Frame {
+            node_mapping,
+            g_mapped,
+            edge_mapping,
+            visited,
+            stack,
+        } = frame;
+
+        // For the next proposed pairing of nodes, ...
+        if let Some((f_node_id, g_node_id)) = self.pop(stack) {
+            trace!("Stack size: {}", stack.len());
+            trace!("Matching node {:?} to {:?}", f_node_id, g_node_id);
 
-        // For each proposed pairing of nodes, ...
-        while let Some((f_node_id, g_node_id)) = stack.pop() {
             // Check node labels are equal
             if self.f.hypergraph.nodes[f_node_id.0] != self.g.hypergraph.nodes[g_node_id.0] {
                 return Err(Error::InvalidNodeMatch(f_node_id, g_node_id));
@@ -141,20 +763,25 @@ impl<'a, O: Eq + Clone + Hash, A: Eq + Clone + Hash> SearchState<'a, O, A> {
 
             // If f_node_id is a source (resp. target) of some edge (monogamicity ⇒ zero or one)
             // then pair that edge with the corresponding one in g (if possible!)
-            for (f_index, g_index) in [
-                (&self.f_index.of_source, &self.g_index.of_source),
-                (&self.f_index.of_target, &self.g_index.of_target),
-            ] {
-                if let Some((f_edge_id, f_port)) = f_index.get(&f_node_id) {
-                    if let Some((g_edge_id, g_port)) = g_index.get(&g_node_id) {
+            for get in [Index::get_source, Index::get_target] {
+                if let Some((f_edge_id, f_port)) = get(&self.f_index, f_node_id) {
+                    if let Some((g_edge_id, g_port)) = get(&self.g_index, g_node_id) {
                         // Check g node is at the same source position
                         if f_port != g_port {
                             return Err(Error::InvalidNodeMatch(f_node_id, g_node_id));
                         }
 
+                        // An edge reached from two of its nodes must be paired the same way both
+                        // times
+                        if let Some(other) = edge_mapping[f_edge_id.0]
+                            && other != g_edge_id
+                        {
+                            return Err(Error::InvalidEdgeMatch(f_edge_id, g_edge_id));
+                        }
+
                         // Identify the f/g edges, and update edge mapping
-                        self.identify_edges(&mut stack, &mut visited, *f_edge_id, *g_edge_id)?;
-                        edge_mapping[f_edge_id.0] = Some(*g_edge_id);
+                        self.identify_edges(stack, visited, f_edge_id, g_edge_id)?;
+                        edge_mapping[f_edge_id.0] = Some(g_edge_id);
                     } else {
                         return Err(Error::InvalidNodeMatch(f_node_id, g_node_id));
                     }
@@ -162,35 +789,33 @@ impl<'a, O: Eq + Clone + Hash, A: Eq + Clone + Hash> SearchState<'a, O, A> {
             }
 
             // Finally, assign the node to the mapping
-            node_mapping[f_node_id.0] = Some(g_node_id);
+            if let Some(previous) = node_mapping[f_node_id.0].replace(g_node_id) {
+                g_mapped[previous.0] = false;
+            }
+            g_mapped[g_node_id.0] = true;
+            return Ok(true);
         }
 
-        // Ensure node mapping is complete
-        // We should have now visited all nodes in the hypergraph.
-        // If some are unvisited, they must not have been reachable from an interface.
-        // In this case, give an error.
-        let node_mapping = node_mapping
-            .iter()
-            .enumerate()
-            .map(|(i, g_node)| g_node.ok_or(Error::UnpairedNode(NodeId(i))))
-            .collect::<Result<_, _>>()?;
-
-        let edge_mapping = edge_mapping
-            .iter()
-            .enumerate()
-            .map(|(i, g_edge)| g_edge.ok_or(Error::UnpairedEdge(EdgeId(i))))
-            .collect::<Result<_, _>>()?;
+        Ok(false)
+    }
 
-        Ok((node_mapping, edge_mapping))
+    /// The next pending pair to process, according to the traversal order
+    fn pop(&self, stack: &mut VecDeque<(NodeId, NodeId)>) -> Option<(NodeId, NodeId)> {
+        match self.order {
+            TraversalOrder::Dfs | TraversalOrder::MostConstrainedFirst => stack.pop_back(),
+            TraversalOrder::Bfs => stack.pop_front(),
+        }
     }
 
     fn identify_edges(
         &self,
-        stack: &mut Vec<(NodeId, NodeId)>,
-        visited: &mut Vec<bool>,
+        stack: &mut VecDeque<(NodeId, NodeId)>,
+        visited: &mut [bool],
         f_edge_id: EdgeId,
         g_edge_id: EdgeId,
     ) -> Result<(), Error> {
+        trace!("Identifying edges {:?}↔{:?}", f_edge_id, g_edge_id);
+
         // Verify f/g have type (including edge label)
         self.ensure_same_type(f_edge_id, g_edge_id)?;
 
@@ -201,7 +826,7 @@ impl<'a, O: Eq + Clone + Hash, A: Eq + Clone + Hash> SearchState<'a, O, A> {
         // Add all pairs to 'stack' and 'visited'
         for (x, y) in f_edge_neighbourhood.iter().zip(g_edge_neighbourhood.iter()) {
             if !visited[x.0] {
-                stack.push((*x, *y));
+                stack.push_back((*x, *y));
                 visited[x.0] = true;
             }
         }
@@ -250,28 +875,90 @@ impl<'a, O: Eq + Clone + Hash, A: Eq + Clone + Hash> SearchState<'a, O, A> {
     }
 }
 
+/// The outcome of [`SearchState::expand`]
+enum Step {
+    /// The frame can't be extended to an isomorphism
+    Dead,
+    /// The frame is a complete isomorphism
+    Found(Isomorphism),
+    /// The frame is stuck, but each of these extensions of it might work
+    Fork(Vec<Frame>),
+}
+
+/// The state of a (partial) traversal
+#[derive(Clone, Default)]
+struct Frame {
+    // The assigned mapping to g.
+    node_mapping: Vec<Option<NodeId>>,
+    // which nodes of g are images in node_mapping
+    g_mapped: Vec<bool>,
+    edge_mapping: Vec<Option<EdgeId>>,
+
+    // which nodes of f have been visited (either in stack, or in node_mapping)
+    visited: Vec<bool>,
+
+    // "stack" is our priority queue of unvisited f nodes.
+    // Each is paired with a single g node.
+    stack: VecDeque<(NodeId, NodeId)>,
+}
+
+impl Frame {
+    /// Start with the *interfaces* of both open hypergraphs on the stack
+    fn new<O, A>(f: &OpenHypergraph<O, A>, g: &OpenHypergraph<O, A>) -> Self {
+        let n = f.hypergraph.nodes.len();
+        let e = f.hypergraph.edges.len();
+
+        let mut stack = VecDeque::new();
+        stack.extend(f.sources.iter().copied().zip(g.sources.iter().copied()));
+        stack.extend(f.targets.iter().copied().zip(g.targets.iter().copied()));
+
+        // Initialize to interfaces since they're already on the stack
+        let mut visited: Vec<bool> = vec![false; n];
+        for &(f_node, _) in &stack {
+            visited[f_node.0] = true;
+        }
+
+        Frame {
+            node_mapping: vec![None; n],
+            g_mapped: vec![false; g.hypergraph.nodes.len()],
+            edge_mapping: vec![None; e],
+            visited,
+            stack,
+        }
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Indexes used during search
 
-struct Index {
-    of_source: HashMap<NodeId, (EdgeId, usize)>,
-    of_target: HashMap<NodeId, (EdgeId, usize)>,
+/// Whether a node appears among the sources or the targets of an edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Source,
+    Target,
+}
+
+/// For each node, the edges (and ports) of which it is a source or target.
+/// In a monogamous hypergraph there is at most one of each.
+pub struct Index {
+    of_source: HashMap<NodeId, Vec<(EdgeId, usize)>>,
+    of_target: HashMap<NodeId, Vec<(EdgeId, usize)>>,
 }
 
 impl Index {
-    fn new<O, A>(hypergraph: &open_hypergraphs::lax::Hypergraph<O, A>) -> Self {
-        let mut of_source = HashMap::new();
-        let mut of_target = HashMap::new();
+    pub fn new<O, A>(hypergraph: &open_hypergraphs::lax::Hypergraph<O, A>) -> Self {
+        let mut of_source: HashMap<_, Vec<_>> = HashMap::new();
+        let mut of_target: HashMap<_, Vec<_>> = HashMap::new();
 
         for (edge_id, adjacency) in hypergraph.adjacency.iter().enumerate() {
             let edge_id = EdgeId(edge_id);
 
             for (port, &node_id) in adjacency.sources.iter().enumerate() {
-                of_source.insert(node_id, (edge_id, port));
+                of_source.entry(node_id).or_default().push((edge_id, port));
             }
 
             for (port, &node_id) in adjacency.targets.iter().enumerate() {
-                of_target.insert(node_id, (edge_id, port));
+                of_target.entry(node_id).or_default().push((edge_id, port));
             }
         }
 
@@ -280,6 +967,32 @@ impl Index {
             of_target,
         }
     }
+
+    /// The edge and port of which `node` is a source (the first, if there are several)
+    pub fn get_source(&self, node: NodeId) -> Option<(EdgeId, usize)> {
+        self.of_source.get(&node)?.first().copied()
+    }
+
+    /// The edge and port of which `node` is a target (the first, if there are several)
+    pub fn get_target(&self, node: NodeId) -> Option<(EdgeId, usize)> {
+        self.of_target.get(&node)?.first().copied()
+    }
+
+    /// Every edge and port at which `node` appears, as a source and then as a target
+    pub fn get_all_edges_for_node(&self, node: NodeId) -> Vec<(EdgeId, usize, Role)> {
+        let sources = self.of_source.get(&node).into_iter().flatten();
+        let targets = self.of_target.get(&node).into_iter().flatten();
+        sources
+            .map(|&(edge_id, port)| (edge_id, port, Role::Source))
+            .chain(targets.map(|&(edge_id, port)| (edge_id, port, Role::Target)))
+            .collect()
+    }
+
+    /// Whether `node` is the source of at most one edge port, and the target of at most one
+    fn is_monogamous_at(&self, node: NodeId) -> bool {
+        let count = |index: &HashMap<NodeId, Vec<_>>| index.get(&node).map_or(0, Vec::len);
+        count(&self.of_source) <= 1 && count(&self.of_target) <= 1
+    }
 }
 
 #[cfg(test)]
@@ -319,7 +1032,6 @@ mod tests {
     /// open hypergraphs should be isomorphic to themselves
     #[test]
     fn test_find_identity_isomorphism() {
-        use open_hypergraphs::category::*;
         let circuits = [
             (
                 "id",
@@ -395,6 +1107,686 @@ mod tests {
         );
     }
 
+    /// Symmetric circuits where the only isomorphism is *not* the identity
+    #[test]
+    fn test_find_non_identity_isomorphism() {
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let circuits = [
+            (
+                "parallel wires",
+                OpenHypergraph::<NodeType, EdgeOp>::identity(vec![
+                    NodeType::Float,
+                    NodeType::Float,
+                ]),
+            ),
+            ("parallel negates", &negate | &negate),
+        ];
+
+        for (name, circuit) in circuits {
+            println!("testing {name}");
+            let num_nodes = circuit.hypergraph.nodes.len();
+            let num_edges = circuit.hypergraph.edges.len();
+
+            // Swap the two halves of the circuit: node i ↦ i + n/2 (mod n)
+            let half = num_nodes / 2;
+            let swap = Permutation::new((0..num_nodes).map(|i| (i + half) % num_nodes))
+                .expect("valid permutation");
+            let isomorphism = Isomorphism {
+                nodes: swap,
+                edges: Permutation::identity(num_edges),
+            };
+            let swapped = isomorphism.apply(&circuit);
+
+            let found_isomorphism =
+                find_isomorphism(&circuit, &swapped).expect("should find isomorphism");
+
+            assert!(
+                !found_isomorphism.nodes.is_identity(),
+                "Should not find identity node permutation"
+            );
+            assert!(
+                found_isomorphism.validate(&circuit, &swapped).is_ok(),
+                "Found isomorphism should be valid"
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_state_indexes() {
+        let circuit = cast_and_negate_then_mul();
+        let state = SearchState::new(&circuit, &circuit).expect("monogamous");
+
+        // the mul edge has the two (unquotiented) nodes 4, 5 as sources, and 6 as target.
+        let mul = EdgeId(2);
+        assert_eq!(state.f_index().get_source(NodeId(4)), Some((mul, 0)));
+        assert_eq!(state.f_index().get_source(NodeId(5)), Some((mul, 1)));
+        assert_eq!(state.g_index().get_target(NodeId(6)), Some((mul, 0)));
+        assert_eq!(state.g_index().get_source(NodeId(6)), None);
+    }
+
+    #[test]
+    fn test_get_all_edges_for_node() {
+        // x is copied into two edges, and is also the output of a third
+        let mut f = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        let x = f.new_node(NodeType::Float);
+        let y = f.new_node(NodeType::Float);
+        f.new_edge(EdgeOp::Negate, (vec![y], vec![x]));
+        f.new_edge(EdgeOp::Mul, (vec![y, x], vec![]));
+        f.new_edge(EdgeOp::Negate, (vec![x], vec![]));
+
+        let index = Index::new(&f.hypergraph);
+        assert_eq!(
+            index.get_all_edges_for_node(x),
+            vec![
+                (EdgeId(1), 1, Role::Source),
+                (EdgeId(2), 0, Role::Source),
+                (EdgeId(0), 0, Role::Target),
+            ]
+        );
+        assert!(
+            index
+                .get_all_edges_for_node(f.new_node(NodeType::Int))
+                .is_empty()
+        );
+
+        // the traversal can't follow both copies of x
+        assert!(matches!(
+            SearchState::new(&f, &f),
+            Err(Error::AlgorithmNotApplicable {
+                reason: NotApplicableReason::NonMonogamous(NodeId(0))
+            })
+        ));
+    }
+
+    #[test]
+    fn test_mismatched_interface_lengths() {
+        let f = OpenHypergraph::<NodeType, EdgeOp>::identity(vec![NodeType::Float]);
+
+        let mut g = f.clone();
+        g.sources.push(NodeId(0));
+        assert!(matches!(
+            SearchState::new(&f, &g),
+            Err(Error::Nogood {
+                reason: NogoodReason::SourceLength
+            })
+        ));
+
+        let mut g = f.clone();
+        g.targets.clear();
+        assert!(matches!(
+            SearchState::new(&f, &g),
+            Err(Error::Nogood {
+                reason: NogoodReason::TargetLength
+            })
+        ));
+    }
+
+    #[test]
+    fn test_find_all_isomorphisms_monogamous() {
+        // Every node is reachable from the interfaces, so the traversal never has a choice
+        let f = cast_and_negate_then_mul();
+        let state = SearchState::new(&f, &f).unwrap();
+        assert_eq!(
+            state.find_all_isomorphisms(),
+            vec![Isomorphism::identity(7, 3)]
+        );
+
+        // ... and non-isomorphic hypergraphs have no isomorphisms at all
+        let mut g = f.clone();
+        g.hypergraph.edges[2] = EdgeOp::Negate;
+        let state = SearchState::new(&f, &g).unwrap();
+        assert!(state.find_all_isomorphisms().is_empty());
+    }
+
+    #[test]
+    fn test_find_all_isomorphisms_symmetric() {
+        // Two disconnected negations with no interfaces: either can be paired with either
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let mut f = &negate | &negate;
+        f.sources.clear();
+        f.targets.clear();
+
+        let state = SearchState::new(&f, &f).unwrap();
+        let mut found = state.find_all_isomorphisms();
+        found.sort_by_key(|iso| iso.edges.to_vec());
+        let swap = Isomorphism {
+            nodes: Permutation::new([2, 3, 0, 1]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        assert_eq!(found, vec![Isomorphism::identity(4, 2), swap]);
+
+        // Three isolated nodes of the same type: all 3! permutations
+        let mut f = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        for _ in 0..3 {
+            f.new_node(NodeType::Int);
+        }
+        let state = SearchState::new(&f, &f).unwrap();
+        assert_eq!(state.find_all_isomorphisms().len(), 6);
+
+        // Two edges with no nodes, e.g. constants of unit type
+        let mut f = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        f.new_edge(EdgeOp::Cast, (vec![], vec![]));
+        f.new_edge(EdgeOp::Cast, (vec![], vec![]));
+        let state = SearchState::new(&f, &f).unwrap();
+        assert_eq!(state.find_all_isomorphisms().len(), 2);
+    }
+
+    #[test]
+    fn test_algorithm_not_applicable() {
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+
+        // The output of negate is also an input
+        let mut f = negate.clone();
+        f.sources.push(NodeId(1));
+        f.targets.push(NodeId(0));
+        assert!(matches!(
+            find_isomorphism(&f, &f),
+            Err(Error::AlgorithmNotApplicable {
+                reason: NotApplicableReason::NonMonogamous(NodeId(1))
+            })
+        ));
+    }
+
+    #[test]
+    fn test_find_isomorphism_disconnected() {
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let cast =
+            OpenHypergraph::singleton(EdgeOp::Cast, vec![NodeType::Int], vec![NodeType::Float]);
+
+        // Nothing is reachable from the (empty) interfaces
+        let mut f = negate.clone();
+        f.sources.clear();
+        f.targets.clear();
+        assert!(find_isomorphism(&f, &f).unwrap().is_identity());
+
+        // One component reachable from the interface and two that aren't, in a different order
+        // in g
+        let mut isolated = &negate | &cast;
+        isolated.sources.clear();
+        isolated.targets.clear();
+        let f = &cast_and_negate_then_mul() | &isolated;
+        let mut isolated = &cast | &negate;
+        isolated.sources.clear();
+        isolated.targets.clear();
+        let g = &cast_and_negate_then_mul() | &isolated;
+        let found = find_isomorphism(&f, &g).unwrap();
+        assert!(found.validate(&f, &g).is_ok());
+        assert_eq!(found.edges, Permutation::new([0, 1, 2, 4, 3]).unwrap());
+
+        // The same nodes and edges, but with the cast feeding the negation instead: the isolated
+        // components of f have no counterparts
+        let mut isolated = OpenHypergraph::empty();
+        let i = isolated.new_node(NodeType::Int);
+        let x = isolated.new_node(NodeType::Float);
+        let y = isolated.new_node(NodeType::Float);
+        isolated.new_node(NodeType::Float);
+        isolated.new_edge(EdgeOp::Cast, (vec![i], vec![x]));
+        isolated.new_edge(EdgeOp::Negate, (vec![x], vec![y]));
+        let g = &cast_and_negate_then_mul() | &isolated;
+        let found = find_isomorphism(&f, &g);
+        assert!(matches!(found, Err(Error::UnmatchedComponent)), "{found:?}");
+    }
+
+    #[test]
+    fn test_find_isomorphism_isolated_nodes() {
+        // Interior nodes with no edges can only be told apart by their labels
+        let mut f = cast_and_negate_then_mul();
+        f.new_node(NodeType::Float);
+        f.new_node(NodeType::Int);
+        f.new_node(NodeType::Float);
+        let mut g = cast_and_negate_then_mul();
+        g.new_node(NodeType::Int);
+        g.new_node(NodeType::Float);
+        g.new_node(NodeType::Float);
+
+        let found = find_isomorphism(&f, &g).unwrap();
+        assert!(found.validate(&f, &g).is_ok());
+        assert_eq!(found.nodes[8], 7);
+
+        // The two isolated floats can be swapped
+        let all = find_all_isomorphisms(&f, &g)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        // No isolated node of g has the label of f's last one: with no edges to go by, this is
+        // already caught by comparing the counts of each label
+        g.hypergraph.nodes[7] = NodeType::Float;
+        let found = find_isomorphism(&f, &g);
+        assert!(matches!(found, Err(Error::Nogood { .. })), "{found:?}");
+    }
+
+    #[test]
+    fn test_duplicate_interface_node() {
+        // An identity wire has the same node as source and target, where a pair of (otherwise
+        // isolated) nodes has different ones
+        let mut id = OpenHypergraph::<NodeType, EdgeOp>::identity(vec![NodeType::Float]);
+        id.new_node(NodeType::Float);
+        let mut pair = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        let x = pair.new_node(NodeType::Float);
+        let y = pair.new_node(NodeType::Float);
+        pair.sources = vec![x];
+        pair.targets = vec![y];
+
+        assert!(find_isomorphism(&id, &id).unwrap().is_identity());
+        let found = SearchState::new(&id, &pair).err();
+        assert!(matches!(
+            found,
+            Some(Error::DuplicateInterfaceNode(NodeId(0)))
+        ));
+        let found = SearchState::new(&pair, &id).err();
+        assert!(matches!(found, Some(Error::DuplicateInterfaceNode(node)) if node == x));
+
+        // Repeats within one interface are checked the same way
+        let mut repeated = id.clone();
+        repeated.sources = vec![NodeId(0), NodeId(0)];
+        repeated.targets = vec![];
+        let mut distinct = id.clone();
+        distinct.sources = vec![NodeId(0), NodeId(1)];
+        distinct.targets = vec![];
+        assert!(
+            find_isomorphism(&repeated, &repeated)
+                .unwrap()
+                .is_identity()
+        );
+        let found = find_isomorphism(&repeated, &distinct);
+        assert!(
+            matches!(found, Err(Error::DuplicateInterfaceNode(NodeId(0)))),
+            "{found:?}"
+        );
+    }
+
+    #[test]
+    fn test_new_seeded() {
+        // Two disconnected negations with no interfaces
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let mut f = &negate | &negate;
+        f.sources.clear();
+        f.targets.clear();
+
+        // Seeding one node pins down its component; the other is still free
+        let mut seed = PartialIsomorphism::empty(4, 2);
+        seed.nodes[0] = Some(2);
+        let state = SearchState::new_seeded(&f, &f, Some(&seed)).unwrap();
+        let swap = Isomorphism {
+            nodes: Permutation::new([2, 3, 0, 1]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        assert_eq!(state.find_all_isomorphisms(), vec![swap.clone()]);
+
+        // Seeding an edge reaches the other component, so the traversal alone succeeds
+        seed.edges[1] = Some(0);
+        let state = SearchState::new_seeded(&f, &f, Some(&seed)).unwrap();
+        let (nodes, edges) = state.find_isomorphism().unwrap();
+        assert_eq!(nodes, vec![NodeId(2), NodeId(3), NodeId(0), NodeId(1)]);
+        assert_eq!(edges, vec![EdgeId(1), EdgeId(0)]);
+
+        // Seeds must agree with the interfaces
+        let f = cast_and_negate_then_mul();
+        let mut seed = PartialIsomorphism::empty(7, 3);
+        seed.nodes[f.sources[0].0] = Some(f.sources[1].0);
+        assert!(matches!(
+            SearchState::new_seeded(&f, &f, Some(&seed)),
+            Err(Error::InvalidNodeMatch(..))
+        ));
+    }
+
+    #[test]
+    fn test_find_isomorphism_from_seed() {
+        // Two disconnected negations with no interfaces
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let mut f = &negate | &negate;
+        f.sources.clear();
+        f.targets.clear();
+
+        let swap = Isomorphism {
+            nodes: Permutation::new([2, 3, 0, 1]).unwrap(),
+            edges: Permutation::new([1, 0]).unwrap(),
+        };
+        let seed = HashMap::from([(NodeId(0), NodeId(2))]);
+        assert_eq!(find_isomorphism_from_seed(&f, &f, seed).unwrap(), swap);
+        let seed = HashMap::new();
+        assert!(
+            find_isomorphism_from_seed(&f, &f, seed)
+                .unwrap()
+                .is_identity()
+        );
+
+        // A source can't correspond to a target
+        let seed = HashMap::from([(NodeId(0), NodeId(1))]);
+        assert!(matches!(
+            find_isomorphism_from_seed(&f, &f, seed),
+            Err(Error::InvalidNodeMatch(..))
+        ));
+        // Two nodes can't have the same image
+        let seed = HashMap::from([(NodeId(0), NodeId(0)), (NodeId(2), NodeId(0))]);
+        assert!(matches!(
+            find_isomorphism_from_seed(&f, &f, seed),
+            Err(Error::InvalidNodeMatch(..))
+        ));
+        // Out of range
+        for seed in [(NodeId(4), NodeId(0)), (NodeId(0), NodeId(4))] {
+            assert!(matches!(
+                find_isomorphism_from_seed(&f, &f, HashMap::from([seed])),
+                Err(Error::InvalidNodeMatch(..))
+            ));
+        }
+        // Seeds must agree with the interfaces
+        let f = cast_and_negate_then_mul();
+        let seed = HashMap::from([(f.sources[0], f.sources[1])]);
+        assert!(find_isomorphism_from_seed(&f, &f, seed).is_err());
+    }
+
+    #[test]
+    fn test_find_structural_isomorphism() {
+        let f = cast_and_negate_then_mul();
+
+        // Relabel every edge as a negation: no longer isomorphic, but the same shape
+        let mut g = f.clone();
+        g.hypergraph.edges = vec![EdgeOp::Negate; 3];
+        assert!(find_isomorphism(&f, &g).is_err());
+        assert!(find_structural_isomorphism(&f, &g).unwrap().is_identity());
+
+        // Node labels are ignored too
+        g.hypergraph.nodes = vec![NodeType::Int; 7];
+        assert!(find_structural_isomorphism(&f, &g).unwrap().is_identity());
+
+        // ... but the shape still has to match
+        g.hypergraph.adjacency[2].sources.pop();
+        assert!(find_structural_isomorphism(&f, &g).is_err());
+    }
+
+    #[test]
+    fn test_find_isomorphism_with_timeout() {
+        // Twelve isolated nodes, where f's last is an Int but g's are all Floats: every pairing
+        // of the Floats fails at the last node, so giving up takes 12! steps
+        let mut f = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        let mut g = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        for i in 0..12 {
+            f.new_node(if i == 11 {
+                NodeType::Int
+            } else {
+                NodeType::Float
+            });
+            g.new_node(NodeType::Float);
+        }
+        g.new_edge(EdgeOp::Cast, (vec![], vec![]));
+        f.new_edge(EdgeOp::Cast, (vec![], vec![]));
+        // The nogood would notice the labels differ, so check the traversal directly
+        let state = SearchState::new(&f, &g).unwrap();
+        let mut limits = Limits {
+            deadline: Some(Instant::now() + Duration::from_millis(10)),
+            ..Limits::default()
+        };
+        assert!(matches!(
+            state.find_isomorphism_within(&mut limits),
+            Ok(None)
+        ));
+
+        let f = cast_and_negate_then_mul();
+        let found = find_isomorphism_with_timeout(&f, &f, Duration::from_secs(60));
+        assert!(found.unwrap().unwrap().is_identity());
+        // The traversal from the interfaces always finishes
+        let found = find_isomorphism_with_timeout(&f, &f, Duration::ZERO);
+        assert!(found.unwrap().unwrap().is_identity());
+        let found = find_isomorphism_with_timeout(&f, &f, Duration::MAX);
+        assert!(found.unwrap().is_some());
+
+        // Nothing reachable from the interfaces, so an expired deadline gives up immediately
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let mut f = negate;
+        f.sources.clear();
+        f.targets.clear();
+        let found = find_isomorphism_with_timeout(&f, &f, Duration::ZERO);
+        assert!(matches!(found, Ok(None)));
+        let found = find_isomorphism_with_timeout(&f, &f, Duration::from_secs(60));
+        assert!(found.unwrap().unwrap().is_identity());
+    }
+
+    #[test]
+    fn test_find_all_isomorphisms_lazily() {
+        // Three isolated nodes of the same type: all 3! permutations, in the same order as
+        // collected by the search state
+        let mut f = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        for _ in 0..3 {
+            f.new_node(NodeType::Int);
+        }
+        let found: Vec<_> = find_all_isomorphisms(&f, &f).map(Result::unwrap).collect();
+        assert_eq!(
+            found,
+            SearchState::new(&f, &f).unwrap().find_all_isomorphisms()
+        );
+        assert_eq!(found.len(), 6);
+        assert_eq!(found.iter().collect::<HashSet<_>>().len(), 6);
+
+        // Only as much of the search as needed is run
+        let mut f = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        for _ in 0..20 {
+            f.new_node(NodeType::Float);
+        }
+        let mut isomorphisms = find_all_isomorphisms(&f, &f);
+        assert!(isomorphisms.next().unwrap().unwrap().is_identity());
+        assert!(!isomorphisms.next().unwrap().unwrap().is_identity());
+
+        // Errors are reported once
+        let g = cast_and_negate_then_mul();
+        let mut isomorphisms = find_all_isomorphisms(&f, &g);
+        assert!(matches!(
+            isomorphisms.next(),
+            Some(Err(Error::Nogood {
+                reason: NogoodReason::NodeCount
+            }))
+        ));
+        assert!(isomorphisms.next().is_none());
+
+        // Every node is reachable from the interfaces, so there is no choice
+        let f = cast_and_negate_then_mul();
+        let found: Vec<_> = find_all_isomorphisms(&f, &f).collect();
+        assert!(matches!(&found[..], [Ok(isomorphism)] if isomorphism.is_identity()));
+    }
+
+    #[test]
+    fn test_verify_isomorphism() {
+        let f = cast_and_negate_then_mul();
+        let found = find_isomorphism(&f, &f).unwrap();
+        assert!(verify_isomorphism(&f, &f, &found).is_ok());
+
+        let swap = Isomorphism::swap_nodes(7, 3, 0, 1).unwrap();
+        assert!(matches!(
+            verify_isomorphism(&f, &f, &swap),
+            Err(Error::Validation(ValidationError::NodeLabel(NodeId(0))))
+        ));
+        assert!(matches!(
+            verify_isomorphism(&f, &f, &Isomorphism::identity(7, 2)),
+            Err(Error::Validation(ValidationError::EdgeCount))
+        ));
+    }
+
+    #[test]
+    fn test_find_isomorphism_with_config() {
+        let orders = [
+            TraversalOrder::Dfs,
+            TraversalOrder::Bfs,
+            TraversalOrder::MostConstrainedFirst,
+        ];
+        let negate =
+            OpenHypergraph::singleton(EdgeOp::Negate, vec![NodeType::Float], vec![NodeType::Float]);
+        let cast =
+            OpenHypergraph::singleton(EdgeOp::Cast, vec![NodeType::Int], vec![NodeType::Float]);
+
+        // One component reachable from the interface and two that aren't
+        let mut isolated = &negate | &cast;
+        isolated.sources.clear();
+        isolated.targets.clear();
+        let f = &cast_and_negate_then_mul() | &isolated;
+        let mut isolated = &cast | &negate;
+        isolated.sources.clear();
+        isolated.targets.clear();
+        let g = &cast_and_negate_then_mul() | &isolated;
+
+        let default = find_isomorphism_with_config(&f, &g, &SearchConfig::default()).unwrap();
+        assert_eq!(default, find_isomorphism(&f, &g).unwrap());
+        for order in orders {
+            let config = SearchConfig {
+                order,
+                ..SearchConfig::default()
+            };
+            let found = find_isomorphism_with_config(&f, &g, &config).unwrap();
+            assert!(found.validate(&f, &g).is_ok(), "{order:?}");
+        }
+
+        // Not isomorphic, since the cast feeds the negation: the search fails after backtracking
+        // at least once
+        let mut isolated = OpenHypergraph::empty();
+        let i = isolated.new_node(NodeType::Int);
+        let x = isolated.new_node(NodeType::Float);
+        let y = isolated.new_node(NodeType::Float);
+        isolated.new_node(NodeType::Float);
+        isolated.new_edge(EdgeOp::Cast, (vec![i], vec![x]));
+        isolated.new_edge(EdgeOp::Negate, (vec![x], vec![y]));
+        let h = &cast_and_negate_then_mul() | &isolated;
+        let limited = SearchConfig {
+            max_backtracks: Some(0),
+            ..SearchConfig::default()
+        };
+        let unlimited = SearchConfig::default();
+        assert!(matches!(
+            find_isomorphism_with_config(&f, &h, &limited),
+            Err(Error::BacktrackLimit)
+        ));
+        assert!(matches!(
+            find_isomorphism_with_config(&f, &h, &unlimited),
+            Err(Error::UnmatchedComponent)
+        ));
+
+        // Two disconnected negations: the seed decides which way round they go
+        let mut f = &negate | &negate;
+        f.sources.clear();
+        f.targets.clear();
+        let config = SearchConfig {
+            seed_hint: Some(HashMap::from([(NodeId(1), NodeId(3))])),
+            ..SearchConfig::default()
+        };
+        let found = find_isomorphism_with_config(&f, &f, &config).unwrap();
+        assert_eq!(found.nodes, Permutation::new([2, 3, 0, 1]).unwrap());
+    }
+
+    #[test]
+    fn test_step() {
+        let f = cast_and_negate_then_mul();
+        let mut state = SearchState::new(&f, &f).unwrap();
+        let mut steps = 0;
+        while state.step().unwrap() {
+            steps += 1;
+            if steps < 7 {
+                assert_eq!(state.result(), None);
+            }
+        }
+        // one step per node
+        assert_eq!(steps, 7);
+        assert!(!state.step().unwrap());
+
+        let (nodes, edges) = state.result().unwrap();
+        assert_eq!(nodes, (0..7).map(NodeId).collect::<Vec<_>>());
+        assert!(state.initial.g_mapped.iter().all(|&mapped| mapped));
+        assert_eq!(edges, (0..3).map(EdgeId).collect::<Vec<_>>());
+
+        // Searching from a partially stepped state gives the same answer
+        let mut state = SearchState::new(&f, &f).unwrap();
+        state.step().unwrap();
+        assert_eq!(
+            state.find_all_isomorphisms(),
+            vec![Isomorphism::identity(7, 3)]
+        );
+
+        // A label mismatch is found when its pair is processed
+        let mut g = f.clone();
+        g.hypergraph.nodes.swap(f.sources[0].0, f.sources[1].0);
+        let mut state = SearchState::new(&f, &g).unwrap();
+        let error = loop {
+            match state.step() {
+                Ok(true) => continue,
+                Ok(false) => panic!("should not match"),
+                Err(error) => break error,
+            }
+        };
+        assert!(matches!(error, Error::InvalidNodeMatch(..)));
+    }
+
+    #[test]
+    fn test_step_error_then_search() {
+        // Two isolated nodes with different labels, seeded with the wrong pairing
+        let mut f = OpenHypergraph::<NodeType, EdgeOp>::empty();
+        f.new_node(NodeType::Int);
+        f.new_node(NodeType::Float);
+        let mut seed = PartialIsomorphism::empty(2, 0);
+        seed.nodes[0] = Some(1);
+        let mut state = SearchState::new_seeded(&f, &f, Some(&seed)).unwrap();
+
+        let error = state.step();
+        assert!(
+            matches!(error, Err(Error::InvalidNodeMatch(..))),
+            "{error:?}"
+        );
+
+        // The failure sticks, rather than the searches carrying on without the failed pairing
+        let error = state.step();
+        assert!(
+            matches!(error, Err(Error::InvalidNodeMatch(..))),
+            "{error:?}"
+        );
+        assert_eq!(state.find_all_isomorphisms(), vec![]);
+        let error = state.find_isomorphism();
+        assert!(
+            matches!(error, Err(Error::InvalidNodeMatch(..))),
+            "{error:?}"
+        );
+        assert_eq!(state.result(), None);
+    }
+
+    #[test]
+    fn test_find_isomorphism_unchecked() {
+        let f = cast_and_negate_then_mul();
+        let found = find_isomorphism_unchecked(&f, &f).unwrap();
+        assert!(found.is_identity());
+
+        // Without the nogood check, a wrong edge label is only noticed during traversal
+        let mut g = f.clone();
+        g.hypergraph.edges[2] = EdgeOp::Negate;
+        assert!(matches!(
+            find_isomorphism(&f, &g),
+            Err(Error::Nogood {
+                reason: NogoodReason::EdgeLabels
+            })
+        ));
+        assert!(matches!(
+            find_isomorphism_unchecked(&f, &g),
+            Err(Error::InvalidEdgeMatch(..))
+        ));
+    }
+
+    #[test]
+    fn test_g_has_extra_node() {
+        let f = cast_and_negate_then_mul();
+
+        // g has an isolated node which no f node could be paired with
+        let mut g = f.clone();
+        g.new_node(NodeType::Float);
+        assert!(matches!(
+            find_isomorphism(&f, &g),
+            Err(Error::Nogood {
+                reason: NogoodReason::NodeCount
+            })
+        ));
+    }
+
     #[test]
     fn test_non_isomorphic_circuits() {
         // Circuit 1: Cast Int -> Float, then negate
@@ -420,7 +1812,7 @@ mod tests {
         let result = find_isomorphism(&circuit1, &circuit2);
 
         assert!(
-            result.is_err(),
+            matches!(result, Err(Error::Nogood { .. })),
             "Should fail to find isomorphism between non-isomorphic circuits"
         );
     }
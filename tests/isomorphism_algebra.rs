@@ -0,0 +1,34 @@
+//! Algebraic laws of isomorphism composition
+use open_hypergraphs_isomorphism::{Isomorphism, Permutation};
+use proptest::prelude::*;
+
+fn permutation(n: usize) -> impl Strategy<Value = Permutation> {
+    Just((0..n).collect::<Vec<_>>())
+        .prop_shuffle()
+        .prop_map(|values| Permutation::new(values).unwrap())
+}
+
+fn isomorphism(num_nodes: usize, num_edges: usize) -> impl Strategy<Value = Isomorphism> {
+    (permutation(num_nodes), permutation(num_edges))
+        .prop_map(|(nodes, edges)| Isomorphism { nodes, edges })
+}
+
+/// Three isomorphisms of the same size
+fn composable_triple() -> impl Strategy<Value = (Isomorphism, Isomorphism, Isomorphism)> {
+    (0..=12usize, 0..=8usize)
+        .prop_flat_map(|(n, e)| (isomorphism(n, e), isomorphism(n, e), isomorphism(n, e)))
+}
+
+proptest! {
+    #[test]
+    fn compose_is_associative((a, b, c) in composable_triple()) {
+        prop_assert_eq!((&a * &b).unwrap() * c.clone(), a.clone() * (&b * &c).unwrap());
+    }
+
+    #[test]
+    fn compose_has_identity((a, _, _) in composable_triple()) {
+        let identity = Isomorphism::identity(a.nodes.len(), a.edges.len());
+        prop_assert_eq!(&(&a * &identity), &Some(a.clone()));
+        prop_assert_eq!(&(&identity * &a), &Some(a.clone()));
+    }
+}
@@ -0,0 +1,102 @@
+//! Round trip: apply a random isomorphism to a random hypergraph, and check that
+//! `find_isomorphism` recovers it.
+use open_hypergraphs::lax::{NodeId, OpenHypergraph};
+use open_hypergraphs_isomorphism::traversal::find_isomorphism;
+use open_hypergraphs_isomorphism::{Isomorphism, Permutation};
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+/// An edge consuming some currently-unconsumed nodes, and producing new ones.
+#[derive(Clone, Debug)]
+struct EdgeRecipe {
+    label: u8,
+    sources: Vec<Index>,
+    targets: Vec<u8>,
+}
+
+fn edge_recipe() -> impl Strategy<Value = EdgeRecipe> {
+    (
+        0..3u8,
+        prop::collection::vec(any::<Index>(), 1..=2),
+        prop::collection::vec(0..3u8, 0..=2),
+    )
+        .prop_map(|(label, sources, targets)| EdgeRecipe {
+            label,
+            sources,
+            targets,
+        })
+}
+
+/// Build a monogamous open hypergraph in which every node is reachable from the interface:
+/// each edge consumes at least one node which is either a source or produced by an earlier edge,
+/// and all unconsumed nodes become targets.
+fn build(inputs: Vec<u8>, edges: Vec<EdgeRecipe>) -> OpenHypergraph<u8, u8> {
+    let mut f = OpenHypergraph::empty();
+    let mut unconsumed: Vec<NodeId> = vec![];
+
+    for label in inputs {
+        let node = f.new_node(label);
+        f.sources.push(node);
+        unconsumed.push(node);
+    }
+
+    for edge in edges {
+        if unconsumed.is_empty() {
+            break;
+        }
+
+        let mut sources = vec![];
+        for index in edge.sources {
+            if unconsumed.is_empty() {
+                break;
+            }
+            sources.push(unconsumed.remove(index.index(unconsumed.len())));
+        }
+        let targets: Vec<NodeId> = edge.targets.into_iter().map(|l| f.new_node(l)).collect();
+        unconsumed.extend(&targets);
+        f.new_edge(edge.label, (sources, targets));
+    }
+
+    f.targets = unconsumed;
+    f
+}
+
+fn permutation(n: usize) -> impl Strategy<Value = Permutation> {
+    Just((0..n).collect::<Vec<_>>())
+        .prop_shuffle()
+        .prop_map(|values| Permutation::new(values).unwrap())
+}
+
+/// A random hypergraph paired with a random isomorphism of its size
+fn hypergraph_and_isomorphism() -> impl Strategy<Value = (OpenHypergraph<u8, u8>, Isomorphism)> {
+    (
+        prop::collection::vec(0..3u8, 1..=4),
+        prop::collection::vec(edge_recipe(), 0..=12),
+    )
+        .prop_map(|(inputs, edges)| build(inputs, edges))
+        .prop_flat_map(|f| {
+            let n = f.hypergraph.nodes.len();
+            let e = f.hypergraph.edges.len();
+            (
+                Just(f),
+                (permutation(n), permutation(e))
+                    .prop_map(|(nodes, edges)| Isomorphism { nodes, edges }),
+            )
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+
+    #[test]
+    fn find_isomorphism_round_trip((f, iso) in hypergraph_and_isomorphism()) {
+        let g = iso.apply(&f);
+
+        let found = find_isomorphism(&f, &g);
+        prop_assert!(found.is_ok(), "find_isomorphism failed: {:?}", found);
+        let found = found.unwrap();
+
+        prop_assert!(found.validate(&f, &g).is_ok());
+        prop_assert_eq!(found.apply(&f), g);
+    }
+}